@@ -0,0 +1,353 @@
+use std::path::Path;
+
+use encoding_rs::Encoding;
+use rayon::prelude::*;
+
+use crate::dpdcmpexcel::{
+    batch::{common_sheets, compare_pairs, file_pairs, TablePair},
+    compares::{CmpOption, Progress},
+    deserializer::{validate, TypeTable},
+    dialect::{parse_dialect_char, CsvOption, DialectOverride},
+    encoding::parse_encoding,
+    errors::{DpdError, DpdResult},
+    filter::FilterExpr,
+    fixedwidth::FixedLayout,
+    schema::SchemaDiff,
+    stream::{compare_streaming, StreamInput},
+    CmpData, Comparison,
+};
+
+pub(crate) const USAGE: &str = "\
+penggunaan: dispendik_compare [SUMBER TARGET [OPSI..]]
+tanpa argumen aplikasi dibuka dalam mode GUI
+SUMBER dan TARGET bisa berupa folder: file dengan nama yang sama dibandingkan berpasangan
+
+OPSI:
+    --sheet NAMA        sheet / tabel sqlite yang dibandingkan (default: sheet pertama)
+    --all-sheets        bandingkan semua sheet / tabel dengan nama yang sama
+    --query SQL         query SELECT untuk input sqlite, menggantikan --sheet
+    --header            baris pertama adalah header
+    --no-header         baris pertama bukan header
+                        (default: dideteksi untuk csv, tidak ada header untuk excel)
+    --delimiter C       delimiter csv, `\t` untuk tab (default: dideteksi otomatis)
+    --quote C           karakter quote csv (default: dideteksi otomatis)
+    --escape C          karakter escape csv (default: quote ganda)
+    --comment C         baris csv yang diawali karakter ini diabaikan
+    --no-flexible       lewati baris csv dengan jumlah kolom berbeda dari baris pertama
+    --encoding NAMA     encoding csv, contoh: utf-8, windows-1252, utf-16le
+                        (default: dideteksi dari BOM / isi file)
+    --layout FILE       baca file text sebagai fixed width, FILE berisi csv
+                        `nama,mulai,lebar,trim` (satu baris per kolom)
+    --filter EKSPRESI   filter baris sebelum dibandingkan, contoh:
+                        --filter 'Nama ~ budi && 3 in 10..20 || Alamat not empty'
+    --algo NAMA         myers | patience | lcs (default: myers)
+    --ignore-num        abaikan kolom pertama (nomor urut)
+    --no-move           jangan deteksi baris yang berpindah posisi
+    --hide-move         sembunyikan baris yang berpindah posisi
+    --no-fast-path      jalankan algoritma diff pada seluruh baris, tanpa melewati
+                        baris identik terlebih dahulu (untuk membandingkan waktu)
+    --stream            untuk csv / tsv yang sangat besar: file dibaca streaming tanpa
+                        dimuat seluruhnya ke memory (tidak bisa digabung dengan --filter)
+";
+
+#[derive(Debug, Default)]
+pub(crate) struct CliArgs {
+    pub source: String,
+    pub target: String,
+    pub sheet: Option<String>,
+    pub all_sheets: bool,
+    pub query: Option<String>,
+    pub filter: Option<String>,
+    pub has_header: Option<bool>,
+    pub delimiter: Option<u8>,
+    pub quote: Option<u8>,
+    pub escape: Option<u8>,
+    pub comment: Option<u8>,
+    pub strict: bool,
+    pub encoding: Option<&'static Encoding>,
+    pub layout: Option<FixedLayout>,
+    pub stream: bool,
+    pub option: CmpOption,
+}
+
+impl CliArgs {
+    /// `None` jika tidak ada opsi dan SUMBER / TARGET tidak diberikan, aplikasi dibuka dalam mode GUI
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> DpdResult<Option<Self>> {
+        let mut out = Self::default();
+        let mut positional = vec![];
+        let mut has_option = false;
+        while let Some(arg) = args.next() {
+            has_option |= arg.starts_with('-');
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| DpdError::Validation(format!("`{}` membutuhkan nilai\n{}", name, USAGE)))
+            };
+            match arg.as_str() {
+                "--sheet" => out.sheet = Some(value("--sheet")?),
+                "--all-sheets" => out.all_sheets = true,
+                "--query" => out.query = Some(value("--query")?),
+                "--filter" => out.filter = Some(value("--filter")?),
+                "--header" => out.has_header = Some(true),
+                "--no-header" => out.has_header = Some(false),
+                "--delimiter" => out.delimiter = Some(dialect_char("--delimiter", &value("--delimiter")?)?),
+                "--quote" => out.quote = Some(dialect_char("--quote", &value("--quote")?)?),
+                "--escape" => out.escape = Some(dialect_char("--escape", &value("--escape")?)?),
+                "--comment" => out.comment = Some(dialect_char("--comment", &value("--comment")?)?),
+                "--no-flexible" => out.strict = true,
+                "--encoding" => {
+                    let label = value("--encoding")?;
+                    out.encoding = Some(parse_encoding(&label).ok_or_else(|| {
+                        DpdError::Validation(format!("Encoding `{}` tidak dikenali\n{}", label, USAGE))
+                    })?);
+                }
+                "--layout" => out.layout = Some(FixedLayout::load(value("--layout")?)?),
+                "--algo" => {
+                    out.option.algoritm = match value("--algo")?.to_lowercase().as_str() {
+                        "myers" => similar::Algorithm::Myers,
+                        "patience" => similar::Algorithm::Patience,
+                        "lcs" => similar::Algorithm::Lcs,
+                        other => {
+                            return Err(DpdError::Validation(format!(
+                                "Algoritma `{}` tidak dikenali\n{}",
+                                other, USAGE
+                            )))
+                        }
+                    }
+                }
+                "--ignore-num" => out.option.ignore_num = true,
+                "--no-move" => out.option.detect_move = false,
+                "--hide-move" => out.option.hide_move = true,
+                "--no-fast-path" => out.option.fast_path = false,
+                "--stream" => out.stream = true,
+                "-h" | "--help" => {
+                    return Err(DpdError::Validation(USAGE.to_owned()));
+                }
+                opt if opt.starts_with("--") => {
+                    return Err(DpdError::Validation(format!(
+                        "Opsi `{}` tidak dikenali\n{}",
+                        opt, USAGE
+                    )))
+                }
+                _ => positional.push(arg),
+            }
+        }
+        match <[String; 2]>::try_from(positional) {
+            Ok([source, target]) => {
+                out.source = source;
+                out.target = target;
+                Ok(Some(out))
+            }
+            Err(p) if p.len() < 2 && !has_option => Ok(None),
+            Err(_) => Err(DpdError::Validation(format!(
+                "SUMBER dan TARGET harus diberikan\n{}",
+                USAGE
+            ))),
+        }
+    }
+
+    /// dialect csv dideteksi dari isi file lalu ditimpa oleh opsi dari argumen
+    fn csv_option(&self, path: &str) -> DpdResult<CsvOption> {
+        if !matches!(validate(path)?, TypeTable::Csv(_)) {
+            return Ok(CsvOption::default());
+        }
+        Ok(CsvOption {
+            dialect: None,
+            overrides: DialectOverride {
+                delimiter: self.delimiter,
+                quote: self.quote,
+                escape: self.escape,
+                has_header: self.has_header,
+                comment: self.comment,
+                flexible: self.strict.then_some(false),
+            },
+            encoding: self.encoding,
+            layout: self.layout.clone(),
+        })
+    }
+
+    fn open(&self, path: &str) -> DpdResult<CmpData> {
+        let data = CmpData::new_with(path, &self.csv_option(path)?)?;
+        for warning in data.warnings.iter() {
+            eprintln!("peringatan [{}] {}", path, warning);
+        }
+        Ok(data)
+    }
+
+    /// pilih sheet lalu terapkan query, header dan filter dari argumen
+    fn select(&self, data: &mut CmpData, sheet: &str) -> DpdResult<()> {
+        match (&self.query, &data.db) {
+            // tabel default tidak perlu dimuat, langsung diganti hasil query
+            (Some(query), Some(_)) => data.load_query(query, &Progress::default())?,
+            _ if !data.sheets.is_empty() => data.set_selected_data(sheet, &Progress::default())?,
+            _ => (),
+        }
+        if let Some(has_header) = self.has_header {
+            data.has_header = has_header;
+        }
+        if let Some(filter) = &self.filter {
+            let header = data.selected_data.first().filter(|_| data.has_header).cloned();
+            let expr = FilterExpr::parse(filter, header.as_deref())?;
+            data.filter(&expr)?;
+        }
+        Ok(())
+    }
+
+    /// tabel yang dibandingkan dari satu pasangan file, dengan `--all-sheets`
+    /// semua sheet bernama sama pada 2 workbook menjadi pasangan masing-masing
+    fn table_pairs(&self, source: &str, target: &str) -> DpdResult<Vec<(TablePair, SchemaDiff)>> {
+        let mut src = self.open(source)?;
+        let mut tgt = self.open(target)?;
+        let first = |data: &CmpData| match &self.sheet {
+            Some(s) => s.to_owned(),
+            None => data.sheets.first().cloned().unwrap_or_default(),
+        };
+        if !(self.all_sheets && src.is_workbook() && tgt.is_workbook()) {
+            let (src_sheet, tgt_sheet) = (first(&src), first(&tgt));
+            self.select(&mut src, &src_sheet)?;
+            self.select(&mut tgt, &tgt_sheet)?;
+            return Ok(vec![TablePair::new(&src_sheet, &tgt_sheet, src, tgt)]);
+        }
+        let sheets = common_sheets(&src, &tgt);
+        if sheets.is_empty() {
+            return Err(DpdError::Validation(format!(
+                "Tidak ada nama sheet yang sama pada `{}` dan `{}`",
+                source, target
+            )));
+        }
+        let mut out = vec![];
+        for sheet in sheets.iter() {
+            self.select(&mut src, sheet)?;
+            self.select(&mut tgt, sheet)?;
+            let (src_rows, tgt_rows) = (
+                std::mem::take(&mut src.selected_data),
+                std::mem::take(&mut tgt.selected_data),
+            );
+            out.push(TablePair::new(
+                sheet,
+                sheet,
+                src.with_rows(sheet, src_rows),
+                tgt.with_rows(sheet, tgt_rows),
+            ));
+        }
+        Ok(out)
+    }
+}
+
+fn dialect_char(name: &str, value: &str) -> DpdResult<u8> {
+    parse_dialect_char(value).ok_or_else(|| {
+        DpdError::Validation(format!(
+            "`{}` membutuhkan satu karakter ascii, ditemukan `{}`",
+            name, value
+        ))
+    })
+}
+
+pub(crate) fn run(args: CliArgs) -> DpdResult<()> {
+    if args.stream {
+        return run_streaming(args);
+    }
+    if args.all_sheets && (args.sheet.is_some() || args.query.is_some()) {
+        return Err(DpdError::Validation(
+            "`--all-sheets` tidak bisa digabung dengan `--sheet` / `--query`".to_owned(),
+        ));
+    }
+    let files = match (Path::new(&args.source).is_dir(), Path::new(&args.target).is_dir()) {
+        (true, true) => file_pairs(&args.source, &args.target)?,
+        (false, false) => vec![(args.source.to_owned(), args.target.to_owned())],
+        _ => {
+            return Err(DpdError::Validation(
+                "SUMBER dan TARGET harus sama-sama file atau sama-sama folder".to_owned(),
+            ))
+        }
+    };
+
+    // setiap pasangan file dimuat dan dibandingkan paralel, output tetap sesuai urutan
+    let loaded = files
+        .par_iter()
+        .map(|(s, t)| args.table_pairs(s, t))
+        .collect::<DpdResult<Vec<_>>>()?;
+    let (pairs, schemas): (Vec<_>, Vec<_>) = loaded.into_iter().flatten().unzip();
+    let results = compare_pairs(args.option, &pairs, &Progress::default())?;
+
+    let many = pairs.len() > 1;
+    for ((pair, schema), comparison) in pairs.iter().zip(schemas.iter()).zip(results) {
+        if many {
+            println!(
+                "######## {} <> {} [{}] ########",
+                pair.src_file, pair.target_file, pair.src_sheet
+            );
+        }
+        print_result(schema, comparison, &pair.src_file, &pair.target_file);
+        if many {
+            println!();
+        }
+    }
+    Ok(())
+}
+
+fn run_streaming(args: CliArgs) -> DpdResult<()> {
+    if args.filter.is_some() {
+        return Err(DpdError::Validation(
+            "`--stream` tidak bisa digabung dengan `--filter`".to_owned(),
+        ));
+    }
+    let mut options = vec![];
+    for path in [&args.source, &args.target] {
+        if !matches!(validate(path)?, TypeTable::Csv(_)) || args.layout.is_some() {
+            return Err(DpdError::Validation(format!(
+                "`--stream` hanya untuk input csv / tsv, `{}` bukan csv",
+                path
+            )));
+        }
+        options.push(args.csv_option(path)?);
+    }
+    let src = StreamInput {
+        file: &args.source,
+        option: &options[0],
+    };
+    let target = StreamInput {
+        file: &args.target,
+        option: &options[1],
+    };
+    let (mut src_preview, mut target_preview) = (src.preview()?, target.preview()?);
+    if let Some(has_header) = args.has_header {
+        src_preview.has_header = has_header;
+        target_preview.has_header = has_header;
+    }
+    let sheet = |data: &CmpData| data.sheets.first().cloned().unwrap_or_default();
+    let (src_sheet, target_sheet) = (sheet(&src_preview), sheet(&target_preview));
+
+    let streamed = compare_streaming(args.option, &src, &target, &src_sheet, &target_sheet)?;
+    for (file, warning) in streamed.warnings.iter() {
+        eprintln!("peringatan [{}] {}", file, warning);
+    }
+    print_result(
+        &SchemaDiff::compare(&src_preview, &target_preview),
+        streamed.comparison,
+        &args.source,
+        &args.target,
+    );
+    Ok(())
+}
+
+fn print_result(schema: &SchemaDiff, comparison: Comparison, src_file: &str, target_file: &str) {
+    if !schema.is_empty() {
+        println!("== PERUBAHAN STRUKTUR KOLOM ==");
+        for change in schema.0.iter() {
+            println!("{}", change);
+        }
+        println!();
+    }
+
+    eprintln!("{}", comparison.stats());
+    let (src_rslt, tgt_rslt) = comparison.get_data();
+    println!("== SUMBER: {} ({} perubahan) ==", src_file, src_rslt.len());
+    for item in src_rslt.iter() {
+        println!("{:<16} {}", item.tag_text(), item);
+    }
+    println!();
+    println!("== TARGET: {} ({} perubahan) ==", target_file, tgt_rslt.len());
+    for item in tgt_rslt.iter() {
+        println!("{:<16} {}", item.tag_text(), item);
+    }
+}
@@ -0,0 +1,178 @@
+use std::{fs, path::Path};
+
+use rayon::prelude::*;
+
+use super::{
+    compares::{CmpOption, Comparison, DiffStats, Progress, Side, Table},
+    errors::{DpdError, DpdResult},
+    schema::SchemaDiff,
+    CmpData,
+};
+
+/// satu pasangan tabel yang siap dibandingkan, data sudah dimuat dari file
+#[derive(Debug, Default, Clone)]
+pub struct TablePair {
+    pub src_sheet: String,
+    pub target_sheet: String,
+    pub src_file: String,
+    pub target_file: String,
+    pub src: Table,
+    pub target: Table,
+}
+
+impl TablePair {
+    /// struktur kolom dibandingkan disini karena `CmpData` tidak bisa dibagi antar thread
+    pub fn new(
+        src_sheet: &str,
+        target_sheet: &str,
+        src: CmpData,
+        target: CmpData,
+    ) -> (Self, SchemaDiff) {
+        let schema = SchemaDiff::compare(&src, &target);
+        let pair = Self {
+            src_sheet: src_sheet.to_owned(),
+            target_sheet: target_sheet.to_owned(),
+            src_file: src.file,
+            target_file: target.file,
+            src: src.selected_data,
+            target: target.selected_data,
+        };
+        (pair, schema)
+    }
+}
+
+/// setiap pasangan dibandingkan paralel di semua core cpu,
+/// urutan hasil selalu sama dengan urutan `pairs`
+pub fn compare_pairs(
+    option: CmpOption,
+    pairs: &[TablePair],
+    progress: &Progress,
+) -> DpdResult<Vec<Comparison>> {
+    progress.add_total(
+        pairs
+            .iter()
+            .map(|p| Comparison::work(p.src.len(), p.target.len()))
+            .sum(),
+    );
+    pairs
+        .par_iter()
+        .map(|p| {
+            Comparison::run(
+                option,
+                Side {
+                    rows: &p.src,
+                    file: &p.src_file,
+                    sheet: &p.src_sheet,
+                },
+                Side {
+                    rows: &p.target,
+                    file: &p.target_file,
+                    sheet: &p.target_sheet,
+                },
+                progress,
+            )
+        })
+        .collect()
+}
+
+/// gabungkan hasil beberapa pasangan sesuai urutannya, waktu dijumlahkan
+pub fn merge(results: Vec<Comparison>) -> Comparison {
+    let mut out = Comparison::default();
+    for Comparison(items, stats) in results {
+        out.0.extend(items);
+        out.1 = sum_stats(&out.1, &stats);
+    }
+    out
+}
+
+fn sum_stats(a: &DiffStats, b: &DiffStats) -> DiffStats {
+    DiffStats {
+        rows: (a.rows.0 + b.rows.0, a.rows.1 + b.rows.1),
+        prefix: a.prefix + b.prefix,
+        suffix: a.suffix + b.suffix,
+        anchored: a.anchored + b.anchored,
+        diffed: (a.diffed.0 + b.diffed.0, a.diffed.1 + b.diffed.1),
+        hash: a.hash + b.hash,
+        diff: a.diff + b.diff,
+        total: a.total + b.total,
+    }
+}
+
+/// nama sheet / tabel yang ada di kedua input, mengikuti urutan sumber
+pub fn common_sheets(src: &CmpData, target: &CmpData) -> Vec<String> {
+    src.sheets
+        .iter()
+        .filter(|s| target.sheets.contains(s))
+        .cloned()
+        .collect()
+}
+
+/// semua sheet dengan nama yang sama di kedua workbook, sheet yang sedang dipilih
+/// memakai data yang sudah di-filter / sort, sheet lain dibaca ulang dari file.
+/// setiap sheet yang selesai dibaca dilaporkan ke `progress`
+pub fn workbook_pairs(
+    src: &mut CmpData,
+    src_sheet: &str,
+    target: &mut CmpData,
+    target_sheet: &str,
+    progress: &Progress,
+) -> DpdResult<Vec<(TablePair, SchemaDiff)>> {
+    let sheets = common_sheets(src, target);
+    if sheets.is_empty() {
+        return Err(DpdError::Validation(format!(
+            "Tidak ada nama sheet yang sama pada `{}` dan `{}`",
+            src.file, target.file
+        )));
+    }
+    progress.add_sheets(sheets.len());
+    let mut out = vec![];
+    for sheet in sheets.iter() {
+        progress.check()?;
+        let src_rows = if sheet == src_sheet {
+            src.selected_data.clone()
+        } else {
+            src.read_sheet(sheet, progress)?.into()
+        };
+        let target_rows = if sheet == target_sheet {
+            target.selected_data.clone()
+        } else {
+            target.read_sheet(sheet, progress)?.into()
+        };
+        progress.sheet_done();
+        out.push(TablePair::new(
+            sheet,
+            sheet,
+            src.with_rows(sheet, src_rows),
+            target.with_rows(sheet, target_rows),
+        ));
+    }
+    Ok(out)
+}
+
+/// pasangan file dengan nama yang sama di 2 folder, diurutkan berdasarkan nama
+pub fn file_pairs<P: AsRef<Path>>(src_dir: P, target_dir: P) -> DpdResult<Vec<(String, String)>> {
+    let mut names = fs::read_dir(src_dir.as_ref())?
+        .filter_map(Result::ok)
+        .filter(|e| e.path().is_file())
+        .map(|e| e.file_name())
+        .collect::<Vec<_>>();
+    names.sort();
+    let pairs = names
+        .into_iter()
+        .filter(|name| target_dir.as_ref().join(name).is_file())
+        .map(|name| {
+            (
+                src_dir.as_ref().join(&name).to_string_lossy().into_owned(),
+                target_dir.as_ref().join(&name).to_string_lossy().into_owned(),
+            )
+        })
+        .collect::<Vec<_>>();
+    if pairs.is_empty() {
+        return Err(DpdError::Validation(format!(
+            "Tidak ada file dengan nama yang sama pada folder `{}` dan `{}`",
+            src_dir.as_ref().display(),
+            target_dir.as_ref().display()
+        )));
+    }
+    Ok(pairs)
+}
@@ -0,0 +1,230 @@
+use std::{cmp::Ordering, fmt};
+
+/// jenis isi cell hasil inferensi dari text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum CellType {
+    #[default]
+    Empty,
+    Number,
+    Date,
+    Text,
+}
+
+impl fmt::Display for CellType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            CellType::Empty => write!(f, "empty"),
+            CellType::Number => write!(f, "number"),
+            CellType::Date => write!(f, "date"),
+            CellType::Text => write!(f, "text"),
+        }
+    }
+}
+
+impl CellType {
+    pub fn infer(cell: &str) -> Self {
+        if cell.trim().is_empty() {
+            CellType::Empty
+        } else if parse_number(cell).is_some() {
+            CellType::Number
+        } else if parse_date(cell).is_some() {
+            CellType::Date
+        } else {
+            CellType::Text
+        }
+    }
+
+    /// jenis satu kolom, cell kosong diabaikan dan minimal 90% cell harus sejenis,
+    /// selain itu dianggap `Text`
+    pub fn infer_column<'a, I>(cells: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let (mut total, mut number, mut date) = (0usize, 0usize, 0usize);
+        for cell in cells {
+            match CellType::infer(cell) {
+                CellType::Empty => continue,
+                CellType::Number => number += 1,
+                CellType::Date => date += 1,
+                CellType::Text => (),
+            }
+            total += 1;
+        }
+        if total == 0 {
+            CellType::Empty
+        } else if number * 10 >= total * 9 {
+            CellType::Number
+        } else if date * 10 >= total * 9 {
+            CellType::Date
+        } else {
+            CellType::Text
+        }
+    }
+}
+
+/// angka biasa (`1234.5`), format lokal dengan koma desimal (`1234,5`) atau dengan
+/// pemisah ribuan (`1.234.567,5` / `1,234,567.5`). satu koma tanpa titik selalu
+/// dianggap koma desimal
+pub fn parse_number(cell: &str) -> Option<f64> {
+    let cell = cell.trim();
+    if cell.is_empty() {
+        return None;
+    }
+    if let Ok(n) = cell.parse::<f64>() {
+        return n.is_finite().then_some(n);
+    }
+    let (commas, dots) = (cell.matches(',').count(), cell.matches('.').count());
+    let (group, decimal) = match (commas, dots) {
+        (1, 0) => (None, Some(',')),
+        (_, 0) => (Some(','), None),
+        (0, _) => (Some('.'), None),
+        _ if cell.rfind(',') > cell.rfind('.') => (Some('.'), Some(',')),
+        _ => (Some(','), Some('.')),
+    };
+    let (int, frac) = match decimal {
+        Some(d) => cell.rsplit_once(d)?,
+        None => (cell, ""),
+    };
+    let digits = int.strip_prefix(['-', '+']).unwrap_or(int);
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    let grouped = match group {
+        Some(g) => {
+            let mut groups = digits.split(g);
+            let first = groups.next().unwrap_or_default();
+            (1..=3).contains(&first.len())
+                && all_digits(first)
+                && groups.all(|g| g.len() == 3 && all_digits(g))
+        }
+        None => !digits.is_empty() && all_digits(digits),
+    };
+    if !grouped || !all_digits(frac) {
+        return None;
+    }
+    let sign = &int[..int.len() - digits.len()];
+    let digits = digits.replace([',', '.'], "");
+    format!("{}{}.{}", sign, digits, if frac.is_empty() { "0" } else { frac })
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())
+}
+
+/// tanggal `yyyy-mm-dd`, `dd/mm/yyyy` atau `dd-mm-yyyy`, jam di belakangnya diabaikan
+pub fn parse_date(cell: &str) -> Option<(i32, u32, u32)> {
+    let date = cell.trim().split([' ', 'T']).next()?;
+    let parts = date.split(['-', '/', '.']).collect::<Vec<_>>();
+    if parts.len() != 3 {
+        return None;
+    }
+    let (y, m, d) = if parts[0].len() == 4 {
+        (parts[0], parts[1], parts[2])
+    } else if parts[2].len() == 4 {
+        (parts[2], parts[1], parts[0])
+    } else {
+        return None;
+    };
+    let (y, m, d) = (y.parse().ok()?, m.parse().ok()?, d.parse().ok()?);
+    if (1..=12).contains(&m) && (1..=31).contains(&d) {
+        Some((y, m, d))
+    } else {
+        None
+    }
+}
+
+/// serial tanggal excel (jumlah hari sejak 1899-12-30) ke `yyyy-mm-dd[ hh:mm:ss]`
+pub fn excel_serial_to_string(serial: f64) -> String {
+    let days = serial.floor() as i64;
+    let secs = (((serial - serial.floor()) * 86_400.0).round() as i64).min(86_399);
+    // algoritma `civil_from_days` dari Howard Hinnant
+    let z = days - 25_569 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+
+    if secs == 0 {
+        format!("{:04}-{:02}-{:02}", y, m, d)
+    } else {
+        format!(
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            y,
+            m,
+            d,
+            secs / 3_600,
+            secs % 3_600 / 60,
+            secs % 60
+        )
+    }
+}
+
+/// urutan dua cell: angka dengan angka dan tanggal dengan tanggal dibandingkan nilainya,
+/// angka sebelum tanggal sebelum text, cell kosong selalu di akhir
+pub fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (CellType::infer(a), CellType::infer(b)) {
+        (CellType::Empty, CellType::Empty) => Ordering::Equal,
+        (CellType::Empty, _) => Ordering::Greater,
+        (_, CellType::Empty) => Ordering::Less,
+        (CellType::Number, CellType::Number) => match (parse_number(a), parse_number(b)) {
+            (Some(x), Some(y)) => x.total_cmp(&y),
+            _ => Ordering::Equal,
+        },
+        (CellType::Date, CellType::Date) => parse_date(a)
+            .cmp(&parse_date(b))
+            .then_with(|| a.trim().cmp(b.trim())),
+        (CellType::Text, CellType::Text) => a.to_lowercase().cmp(&b.to_lowercase()),
+        (x, y) => x.cmp(&y),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_with_decimal_comma_and_thousands() {
+        assert_eq!(parse_number("1234.5"), Some(1234.5));
+        assert_eq!(parse_number(" 1234,5 "), Some(1234.5));
+        assert_eq!(parse_number("1.234.567"), Some(1_234_567.0));
+        assert_eq!(parse_number("1.234.567,25"), Some(1_234_567.25));
+        assert_eq!(parse_number("1,234,567.25"), Some(1_234_567.25));
+        assert_eq!(parse_number("-12.500"), Some(-12.5));
+        assert_eq!(parse_number("-1.250.000"), Some(-1_250_000.0));
+        assert_eq!(parse_number("1.23.4"), None);
+        assert_eq!(parse_number("12,34,5"), None);
+        assert_eq!(parse_number("1,2.3,4"), None);
+        assert_eq!(parse_number("Rp 1.000"), None);
+        assert_eq!(parse_number("inf"), None);
+    }
+
+    #[test]
+    fn dates_in_both_orders() {
+        assert_eq!(parse_date("2022-10-05"), Some((2022, 10, 5)));
+        assert_eq!(parse_date("05/10/2022"), Some((2022, 10, 5)));
+        assert_eq!(parse_date("05-10-2022 13:45"), Some((2022, 10, 5)));
+        assert_eq!(parse_date("2022-10-05T13:45:00"), Some((2022, 10, 5)));
+        assert_eq!(parse_date("10/13/2022"), None);
+        assert_eq!(parse_date("05/10/22"), None);
+    }
+
+    #[test]
+    fn compare_by_value_then_type() {
+        assert_eq!(compare_cells("9", "10"), Ordering::Less);
+        assert_eq!(compare_cells("1.000.000", "999"), Ordering::Greater);
+        assert_eq!(compare_cells("31/01/2022", "01/02/2022"), Ordering::Less);
+        assert_eq!(compare_cells("budi", "Andi"), Ordering::Greater);
+        let mut column = vec!["teks", "", "2022-01-01", "10", "2"];
+        column.sort_by(|a, b| compare_cells(a, b));
+        assert_eq!(column, vec!["2", "10", "2022-01-01", "teks", ""]);
+    }
+
+    #[test]
+    fn infer_mixed_column() {
+        let column = ["1", "2", "", "3", "x"];
+        assert_eq!(CellType::infer_column(column), CellType::Text);
+        let mostly = ["1"; 9].into_iter().chain(["x"]);
+        assert_eq!(CellType::infer_column(mostly), CellType::Number);
+    }
+}
@@ -1,24 +1,157 @@
 use super::{
-    deserializer::{deserialize_data_excel, validate},
-    errors::{DpdError, DpdResult},
-    SortVec,
+    deserializer::{deserialize_data_csv, deserialize_data_excel, validate},
+    dialect::{CsvDialect, CsvOption},
+    encoding::open_decoded,
+    errors::{DpdError, DpdResult, LoadWarning},
+    filter::FilterExpr,
+    fixedwidth::{load_fixed_width, FixedLayout},
+    format::open_workbook_as,
+    json::{load_json, load_json_lines},
+    paste::parse_clipboard,
+    sqlite,
+    transform::Transform,
+    SortKey,
 };
 use crate::dpdcmpexcel::CmpRslt;
-use calamine::{open_workbook_auto, Reader, Sheets};
-use similar::{capture_diff_slices, Algorithm, ChangeTag};
-use std::path::Path;
+use calamine::{Reader, Sheets};
+use encoding_rs::Encoding;
+use rusqlite::Connection;
+use similar::{capture_diff, capture_diff_slices, Algorithm, ChangeTag, DiffOp, DiffTag};
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeSet, HashMap, VecDeque},
+    fmt,
+    hash::{BuildHasherDefault, Hash, Hasher},
+    ops::Range,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+/// jumlah baris yang di-hash sebelum progres dilaporkan dan pembatalan dicek
+pub(crate) const PROGRESS_CHUNK: usize = 4096;
+
+/// isi tabel, dibagi ke thread perbandingan tanpa di-copy.
+/// perubahan memakai `Arc::make_mut` sehingga hanya di-copy jika masih dipakai thread lain
+pub type Table = Arc<Vec<Vec<String>>>;
+
+/// progres perbandingan yang bisa dibaca dan dibatalkan dari thread lain,
+/// satuan kerja adalah baris: setiap baris dihitung sekali saat hash dan sekali saat diff
+#[derive(Debug, Default)]
+pub struct Progress {
+    done: AtomicUsize,
+    total: AtomicUsize,
+    cancelled: AtomicBool,
+    sheets_done: AtomicUsize,
+    sheets_total: AtomicUsize,
+}
+
+impl Progress {
+    #[inline]
+    pub fn add_total(&self, work: usize) {
+        self.total.fetch_add(work, Ordering::Relaxed);
+    }
+    #[inline]
+    pub fn advance(&self, work: usize) {
+        self.done.fetch_add(work, Ordering::Relaxed);
+    }
+    /// `None` jika jumlah kerja belum diketahui
+    pub fn fraction(&self) -> Option<f32> {
+        let total = self.total.load(Ordering::Relaxed);
+        (total > 0).then(|| self.done.load(Ordering::Relaxed) as f32 / total as f32)
+    }
+    /// jumlah sheet yang dibaca sebelum perbandingan dimulai
+    #[inline]
+    pub fn add_sheets(&self, sheets: usize) {
+        self.sheets_total.fetch_add(sheets, Ordering::Relaxed);
+    }
+    #[inline]
+    pub fn sheet_done(&self) {
+        self.sheets_done.fetch_add(1, Ordering::Relaxed);
+    }
+    /// `(sheet selesai, jumlah sheet)` selama masih ada sheet yang dibaca
+    pub fn sheets(&self) -> Option<(usize, usize)> {
+        let total = self.sheets_total.load(Ordering::Relaxed);
+        let done = self.sheets_done.load(Ordering::Relaxed);
+        (done < total).then_some((done, total))
+    }
+    #[inline]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+    pub(crate) fn check(&self) -> DpdResult<()> {
+        if self.is_cancelled() {
+            Err(DpdError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// satu sisi perbandingan: isi tabel beserta file dan sheet asalnya
+#[derive(Debug, Clone, Copy)]
+pub struct Side<'a> {
+    pub rows: &'a [Vec<String>],
+    pub file: &'a str,
+    pub sheet: &'a str,
+}
 
 #[allow(unused)]
 #[derive(Debug)]
-pub struct Comparison(pub Vec<CmpRslt>);
+pub struct Comparison(pub Vec<CmpRslt>, pub DiffStats);
 
 impl Default for Comparison {
     fn default() -> Self {
-        Self(Vec::default())
+        Self(Vec::default(), DiffStats::default())
+    }
+}
+
+/// ringkasan kerja diff, untuk melihat seberapa banyak baris yang dilewati fast path
+#[derive(Debug, Default, Clone)]
+pub struct DiffStats {
+    pub rows: (usize, usize),
+    /// baris identik di awal dan akhir tabel
+    pub prefix: usize,
+    pub suffix: usize,
+    /// baris unik yang identik di kedua tabel dan urutannya sama
+    pub anchored: usize,
+    /// jumlah baris (sumber, target) yang diproses algoritma diff
+    pub diffed: (usize, usize),
+    pub hash: Duration,
+    pub diff: Duration,
+    pub total: Duration,
+}
+
+impl fmt::Display for DiffStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "baris {} / {} | prefix {}, suffix {}, anchor {} | diff {} / {} baris | hash {:.1?}, diff {:.1?}, total {:.1?}",
+            self.rows.0,
+            self.rows.1,
+            self.prefix,
+            self.suffix,
+            self.anchored,
+            self.diffed.0,
+            self.diffed.1,
+            self.hash,
+            self.diff,
+            self.total
+        )
     }
 }
 
 impl Comparison {
+    #[inline]
+    pub fn stats(&self) -> &DiffStats {
+        &self.1
+    }
     pub fn get_data(self) -> (Vec<CmpRslt>, Vec<CmpRslt>) {
         let src = self
             .0
@@ -44,59 +177,434 @@ impl Comparison {
             .collect::<Vec<_>>();
         (src, tgt)
     }
-    #[allow(unused)]
-    pub fn run(
-        algortm: Algorithm,
-        src: &Vec<Vec<String>>,
-        target: &Vec<Vec<String>>,
-        sheet: &str,
-        src_file: &str,
-        target_file: &str,
-        ignore_num: bool,
-    ) -> DpdResult<Self> {
+    /// jumlah kerja untuk `Progress::add_total`
+    #[inline]
+    pub fn work(src_rows: usize, target_rows: usize) -> usize {
+        2 * (src_rows + target_rows)
+    }
+
+    /// diff dilakukan pada fingerprint baris, isi tabel tidak di-copy,
+    /// hanya baris yang berubah yang disalin ke hasil.
+    /// total kerja pada `progress` diisi oleh pemanggil.
+    /// setiap baris hasil diberi nama file dan sheet dari sisinya masing-masing
+    pub fn run(option: CmpOption, src: Side, target: Side, progress: &Progress) -> DpdResult<Self> {
+        let start = Instant::now();
+        let fingerprints = |rows: &[Vec<String>]| -> DpdResult<Vec<u64>> {
+            let mut out = Vec::with_capacity(rows.len());
+            for chunk in rows.chunks(PROGRESS_CHUNK) {
+                progress.check()?;
+                out.extend(chunk.iter().map(|r| row_hash(r, option.ignore_num)));
+                progress.advance(chunk.len());
+            }
+            Ok(out)
+        };
+        let (old, new) = (fingerprints(src.rows)?, fingerprints(target.rows)?);
+        let mut stats = DiffStats {
+            hash: start.elapsed(),
+            ..Default::default()
+        };
+        let cells = |row: &Vec<String>| {
+            if option.ignore_num {
+                row.get(1..).unwrap_or_default().to_owned()
+            } else {
+                row.to_owned()
+            }
+        };
+        let out = diff_fingerprints(&option, &old, &new, &mut stats, progress)?
+            .changes(&option, |o, n| {
+                is_related(&src.rows[o], &target.rows[n], option.ignore_num)
+            })
+            .into_iter()
+            .map(|change| {
+                let (own, other) = if change.issrc {
+                    (src, target)
+                } else {
+                    (target, src)
+                };
+                CmpRslt {
+                    issrc: change.issrc,
+                    index: change.index,
+                    tag: change.tag,
+                    file: own.file.to_owned(),
+                    sheet: own.sheet.to_owned(),
+                    data: cells(&own.rows[change.index]),
+                    pair: change.pair.map(|i| cells(&other.rows[i])),
+                    moved: change.moved,
+                }
+            })
+            .collect();
+        stats.total = start.elapsed();
+        Ok(Self(out, stats))
+    }
+}
+
+/// baris yang berubah hasil diff fingerprint, isi baris diambil dari sumbernya oleh pemanggil
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RowChange {
+    pub issrc: bool,
+    pub index: usize,
+    pub tag: ChangeTag,
+    /// index baris pasangan di sisi lain pada `DiffOp::Replace`
+    pub pair: Option<usize>,
+    pub moved: Option<(usize, usize)>,
+}
+
+/// fingerprint 64 bit isi baris, `ignore_num` mengabaikan kolom pertama
+pub(crate) fn row_hash<S: AsRef<str>>(row: &[S], ignore_num: bool) -> u64 {
+    let row = if ignore_num {
+        row.get(1..).unwrap_or_default()
+    } else {
+        row
+    };
+    let mut hasher = DefaultHasher::new();
+    hasher.write_usize(row.len());
+    for cell in row {
+        cell.as_ref().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// hasil diff fingerprint sebelum baris MODIFIED dipasangkan, pemasangan butuh isi baris
+pub(crate) struct RowDiff {
+    ops: Vec<DiffOp>,
+    moves: HashMap<usize, usize>,
+}
+
+pub(crate) fn diff_fingerprints(
+    option: &CmpOption,
+    old: &[u64],
+    new: &[u64],
+    stats: &mut DiffStats,
+    progress: &Progress,
+) -> DpdResult<RowDiff> {
+    let start = Instant::now();
+    stats.rows = (old.len(), new.len());
+    let ops = if option.fast_path {
+        fast_diff_ops(option.algoritm, old, new, stats, progress)?
+    } else {
+        stats.diffed = stats.rows;
+        let ops = capture_diff_slices(option.algoritm, old, new);
+        progress.advance(old.len() + new.len());
+        ops
+    };
+    progress.check()?;
+    let moves = if option.detect_move {
+        find_moves(&ops, old, new)
+    } else {
+        HashMap::new()
+    };
+    stats.diff = start.elapsed();
+    Ok(RowDiff { ops, moves })
+}
+
+impl RowDiff {
+    /// index baris (sumber, target) yang tidak identik
+    pub fn changed_rows(&self) -> (BTreeSet<usize>, BTreeSet<usize>) {
+        let (mut old, mut new) = (BTreeSet::new(), BTreeSet::new());
+        for op in self.ops.iter() {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+            if tag != DiffTag::Equal {
+                old.extend(old_range);
+                new.extend(new_range);
+            }
+        }
+        (old, new)
+    }
+
+    /// `related(index lama, index baru)` menentukan apakah 2 baris pada `DiffOp::Replace`
+    /// adalah baris yang sama yang isinya diubah
+    pub fn changes<F>(&self, option: &CmpOption, related: F) -> Vec<RowChange>
+    where
+        F: Fn(usize, usize) -> bool,
+    {
+        let moves = &self.moves;
+        let moved_new = moves
+            .iter()
+            .map(|(&o, &n)| (n, o))
+            .collect::<HashMap<usize, usize>>();
+        let pairs = replaced_pairs(&self.ops, moves, &moved_new, related);
+        let pairs_new = pairs
+            .iter()
+            .map(|(&o, &n)| (n, o))
+            .collect::<HashMap<usize, usize>>();
+
         let mut out = vec![];
-        let (old_data, new_data) = if ignore_num {
-            (
-                src.into_iter()
-                    .map(|x| x[1..].to_owned())
-                    .collect::<Vec<_>>(),
-                target
-                    .into_iter()
-                    .map(|x| x[1..].to_owned())
-                    .collect::<Vec<_>>(),
-            )
+        for op in self.ops.iter() {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+            if tag == DiffTag::Equal {
+                continue;
+            }
+            let deleted = old_range.map(|index| RowChange {
+                issrc: true,
+                index,
+                tag: ChangeTag::Delete,
+                pair: pairs.get(&index).copied(),
+                moved: moves.get(&index).map(|&to| (index, to)),
+            });
+            let inserted = new_range.map(|index| RowChange {
+                issrc: false,
+                index,
+                tag: ChangeTag::Insert,
+                pair: pairs_new.get(&index).copied(),
+                moved: moved_new.get(&index).map(|&from| (from, index)),
+            });
+            out.extend(
+                deleted
+                    .chain(inserted)
+                    .filter(|change| !(change.moved.is_some() && option.hide_move)),
+            );
+        }
+        out
+    }
+}
+
+/// prefix / suffix yang identik dibuang, lalu baris yang unik di kedua tabel dan urutannya
+/// sama dijadikan anchor, algoritma diff hanya dijalankan pada celah diantara anchor
+fn fast_diff_ops(
+    alg: Algorithm,
+    old: &[u64],
+    new: &[u64],
+    stats: &mut DiffStats,
+    progress: &Progress,
+) -> DpdResult<Vec<DiffOp>> {
+    let prefix = old.iter().zip(new.iter()).take_while(|(o, n)| o == n).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(o, n)| o == n)
+        .count();
+    let old_range = prefix..old.len() - suffix;
+    let new_range = prefix..new.len() - suffix;
+    let anchors = unique_anchors(old, old_range.clone(), new, new_range.clone());
+    stats.prefix = prefix;
+    stats.suffix = suffix;
+    stats.anchored = anchors.len();
+    progress.advance(2 * prefix);
+
+    let mut ops = vec![];
+    let (mut o, mut n) = (old_range.start, new_range.start);
+    let end = (old_range.end, new_range.end);
+    for (ao, an) in anchors.into_iter().chain(std::iter::once(end)) {
+        if o < ao || n < an {
+            progress.check()?;
+            stats.diffed.0 += ao - o;
+            stats.diffed.1 += an - n;
+            ops.extend(capture_diff(alg, old, o..ao, new, n..an));
+            progress.advance((ao - o) + (an - n));
+        }
+        if (ao, an) != end {
+            progress.advance(2);
+        }
+        (o, n) = (ao + 1, an + 1);
+    }
+    progress.advance(2 * suffix);
+    Ok(ops)
+}
+
+/// fingerprint sudah berupa hash, tidak perlu di-hash ulang sebagai key `HashMap`
+#[derive(Default)]
+struct Fingerprint(u64);
+
+impl Hasher for Fingerprint {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = self.0.rotate_left(8) ^ u64::from(*b);
+        }
+    }
+    fn write_u64(&mut self, i: u64) {
+        self.0 = i;
+    }
+}
+
+/// pasangan `(index lama, index baru)` baris yang muncul tepat sekali di kedua range,
+/// diambil subsequence terpanjang yang urutannya sama di kedua tabel
+fn unique_anchors(
+    old: &[u64],
+    old_range: Range<usize>,
+    new: &[u64],
+    new_range: Range<usize>,
+) -> Vec<(usize, usize)> {
+    let mut seen: HashMap<u64, (usize, usize, usize), BuildHasherDefault<Fingerprint>> =
+        HashMap::with_capacity_and_hasher(old_range.len(), Default::default());
+    for idx in old_range {
+        let entry = seen.entry(old[idx]).or_insert((0, idx, 0));
+        entry.0 += 1;
+    }
+    let mut candidates = vec![];
+    for idx in new_range {
+        if let Some(entry) = seen.get_mut(&new[idx]) {
+            entry.2 += 1;
+            if entry.2 == 1 {
+                candidates.push(idx);
+            }
+        }
+    }
+    let mut candidates = candidates
+        .into_iter()
+        .filter_map(|n| {
+            let (old_count, o, new_count) = seen[&new[n]];
+            (old_count == 1 && new_count == 1).then_some((o, n))
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_unstable();
+
+    // longest increasing subsequence pada index baru (patience sorting)
+    let mut tails: Vec<usize> = vec![];
+    let mut prev = vec![usize::MAX; candidates.len()];
+    for (i, &(_, n)) in candidates.iter().enumerate() {
+        let pos = tails.partition_point(|&t| candidates[t].1 < n);
+        if pos > 0 {
+            prev[i] = tails[pos - 1];
+        }
+        if pos == tails.len() {
+            tails.push(i);
         } else {
-            (src.clone(), target.clone())
-        };
+            tails[pos] = i;
+        }
+    }
+    let mut out = vec![];
+    let mut cur = tails.last().copied().unwrap_or(usize::MAX);
+    while cur != usize::MAX {
+        out.push(candidates[cur]);
+        cur = prev[cur];
+    }
+    out.reverse();
+    out
+}
 
-        for (idx, op) in capture_diff_slices(algortm, &old_data, &new_data)
-            .into_iter()
-            .enumerate()
+#[derive(Debug, Clone, Copy)]
+pub struct CmpOption {
+    pub algoritm: Algorithm,
+    pub ignore_num: bool,
+    /// baris identik yang hanya berpindah posisi dilaporkan sebagai `Moved`
+    pub detect_move: bool,
+    /// baris `Moved` tidak dimasukkan ke output
+    pub hide_move: bool,
+    /// lewati prefix / suffix dan baris unik yang identik sebelum menjalankan algoritma diff
+    pub fast_path: bool,
+}
+
+impl Default for CmpOption {
+    fn default() -> Self {
+        Self {
+            algoritm: Algorithm::Myers,
+            ignore_num: false,
+            detect_move: true,
+            hide_move: false,
+            fast_path: true,
+        }
+    }
+}
+
+/// baris yang dihapus di satu posisi dan muncul identik di posisi lain,
+/// dipasangkan berurutan: `index lama -> index baru`
+fn find_moves<T: Eq + Hash>(ops: &[DiffOp], old: &[T], new: &[T]) -> HashMap<usize, usize> {
+    let mut deleted: HashMap<&T, VecDeque<usize>> = HashMap::new();
+    for op in ops {
+        if let DiffOp::Delete {
+            old_index, old_len, ..
+        }
+        | DiffOp::Replace {
+            old_index, old_len, ..
+        } = *op
         {
-            for change in op.iter_changes(&old_data, &new_data) {
-                match change.tag() {
-                    ChangeTag::Equal => continue,
-                    tag => {
-                        let (file, issrc, index) = if let Some(idx) = change.old_index() {
-                            (src_file, true, idx)
-                        } else {
-                            (target_file, false, change.new_index().unwrap_or(0))
-                        };
-                        let data = change.value();
-                        out.push(CmpRslt {
-                            issrc,
-                            index,
-                            tag,
-                            file: file.to_owned(),
-                            sheet: sheet.to_owned(),
-                            data: data.to_owned(),
-                        });
-                    }
-                };
+            for (idx, row) in old.iter().enumerate().skip(old_index).take(old_len) {
+                deleted.entry(row).or_default().push_back(idx);
             }
         }
-        Ok(Self(out))
     }
+
+    let mut moves = HashMap::new();
+    for op in ops {
+        if let DiffOp::Insert {
+            new_index, new_len, ..
+        }
+        | DiffOp::Replace {
+            new_index, new_len, ..
+        } = *op
+        {
+            for (idx, row) in new.iter().enumerate().skip(new_index).take(new_len) {
+                if let Some(from) = deleted.get_mut(row).and_then(|q| q.pop_front()) {
+                    moves.insert(from, idx);
+                }
+            }
+        }
+    }
+    moves
+}
+
+/// baris baru yang diperiksa untuk setiap baris lama saat mencari pasangan
+const PAIR_LOOKAHEAD: usize = 8;
+
+/// rata-rata kemiripan cell minimal agar 2 baris dianggap baris yang sama
+const PAIR_SIMILARITY: f32 = 0.5;
+
+/// pasangan baris pada `DiffOp::Replace` (`index lama -> index baru`), setiap baris yang
+/// dihapus dipasangkan dengan baris tambahan berikutnya yang mirip (`related`) sehingga urutan
+/// tetap terjaga. baris yang tidak punya pasangan tetap DELETED / INSERTED, baris `Moved` tidak ikut
+fn replaced_pairs<F>(
+    ops: &[DiffOp],
+    moves: &HashMap<usize, usize>,
+    moved_new: &HashMap<usize, usize>,
+    related: F,
+) -> HashMap<usize, usize>
+where
+    F: Fn(usize, usize) -> bool,
+{
+    let mut pairs = HashMap::new();
+    for op in ops {
+        if let DiffOp::Replace {
+            old_index,
+            old_len,
+            new_index,
+            new_len,
+        } = *op
+        {
+            let news = (new_index..new_index + new_len)
+                .filter(|i| !moved_new.contains_key(i))
+                .collect::<Vec<_>>();
+            let mut cursor = 0;
+            for old in (old_index..old_index + old_len).filter(|i| !moves.contains_key(i)) {
+                let window = cursor..news.len().min(cursor + PAIR_LOOKAHEAD);
+                if let Some(k) = window.into_iter().find(|&k| related(old, news[k])) {
+                    pairs.insert(old, news[k]);
+                    cursor = k + 1;
+                }
+            }
+        }
+    }
+    pairs
+}
+
+/// rata-rata kemiripan per cell (0..1), cell yang berbeda dinilai dengan diff per karakter
+pub(crate) fn row_similarity<S: AsRef<str>>(old: &[S], new: &[S]) -> f32 {
+    let len = old.len().max(new.len());
+    if len == 0 {
+        return 1.0;
+    }
+    let total = old
+        .iter()
+        .zip(new.iter())
+        .map(|(o, n)| {
+            let (o, n) = (o.as_ref(), n.as_ref());
+            if o == n {
+                1.0
+            } else {
+                similar::TextDiff::from_chars(o, n).ratio()
+            }
+        })
+        .sum::<f32>();
+    total / len as f32
+}
+
+/// 2 baris dianggap baris yang sama yang diubah, `ignore_num` mengabaikan kolom pertama
+pub(crate) fn is_related<S: AsRef<str>>(old: &[S], new: &[S], ignore_num: bool) -> bool {
+    let skip = |row: &[S]| -> usize { usize::from(ignore_num).min(row.len()) };
+    row_similarity(&old[skip(old)..], &new[skip(new)..]) >= PAIR_SIMILARITY
 }
 
 #[derive(Debug, Default)]
@@ -116,30 +624,52 @@ impl From<(usize, usize)> for SizeTable {
 pub struct CmpData {
     pub file: String,
     pub exl: Option<Sheets>,
+    pub db: Option<Connection>,
     pub sheets: Vec<String>,
-    pub selected_data: Vec<Vec<String>>,
+    pub selected_data: Table,
     pub size: SizeTable,
     pub has_header: bool,
     pub is_filtered: bool,
+    pub dialect: Option<CsvDialect>,
+    pub encoding: Option<&'static Encoding>,
+    pub warnings: Vec<LoadWarning>,
+    pub layout: Option<FixedLayout>,
+    original: Table,
+    history: Vec<Transform>,
+    redo: Vec<Transform>,
+    generation: u64,
 }
 impl std::fmt::Debug for CmpData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("CmpData")
             .field("file", &self.file)
             .field("exl", &"Option<Sheets>")
+            .field("db", &"Option<Connection>")
             .field("sheets", &self.sheets)
             .field("selected_data", &self.selected_data)
             .field("size", &self.size)
             .field("has_header", &self.has_header)
             .field("is_filtered", &self.is_filtered)
+            .field("dialect", &self.dialect)
+            .field("encoding", &self.encoding.map(Encoding::name))
+            .field("warnings", &self.warnings)
+            .field("layout", &self.layout)
+            .field("history", &self.history)
+            .field("redo", &self.redo)
+            .field("generation", &self.generation)
             .finish()
     }
 }
 
 impl CmpData {
+    #[inline]
     pub fn new<P: AsRef<Path>>(f: P) -> DpdResult<Self> {
+        Self::new_with(f, &CsvOption::default())
+    }
+
+    pub fn new_with<P: AsRef<Path>>(f: P, option: &CsvOption) -> DpdResult<Self> {
         match validate(f.as_ref())? {
-            super::deserializer::TypeTable::Excel(s) => match open_workbook_auto(&s) {
+            super::deserializer::TypeTable::Excel(s, format) => match open_workbook_as(&s, format) {
                 Ok(exl) => {
                     let sheets = exl.sheet_names().to_owned();
                     Ok(Self {
@@ -154,28 +684,132 @@ impl CmpData {
                     s, e
                 ))),
             },
+            super::deserializer::TypeTable::Sqlite(s) => {
+                let db = sqlite::open(&s)?;
+                let sheets = sqlite::table_names(&db)?;
+                if sheets.is_empty() {
+                    return Err(DpdError::Validation(format!(
+                        "Database `{}` tidak memiliki tabel",
+                        s
+                    )));
+                }
+                Ok(Self {
+                    file: s,
+                    db: Some(db),
+                    sheets,
+                    ..Default::default()
+                })
+            }
+            super::deserializer::TypeTable::Json(s) => {
+                let data = load_json(&s)?;
+                Ok(Self::from_records(s, data, vec![]))
+            }
+            super::deserializer::TypeTable::JsonLines(s) => {
+                let (data, warnings) = load_json_lines(&s)?;
+                Ok(Self::from_records(s, data, warnings))
+            }
             super::deserializer::TypeTable::Csv(s) => {
-                let mut reader = csv::Reader::from_path(&s)?;
-                let data: Vec<Vec<String>> = reader.deserialize().filter_map(|f| f.ok()).collect();
+                let encoding = option.resolve_encoding(&s)?;
+                if let Some(layout) = &option.layout {
+                    let (data, warnings) = load_fixed_width(&s, encoding, layout)?;
+                    let mut out = Self::from_records(s, data, warnings);
+                    out.encoding = Some(encoding);
+                    out.layout = Some(layout.to_owned());
+                    return Ok(out);
+                }
+                let dialect = option.resolve_dialect(&s, encoding)?;
+                let mut reader = dialect
+                    .reader_builder()
+                    .from_reader(open_decoded(&s, encoding)?);
+                let (data, warnings) = deserialize_data_csv(&mut reader);
                 let size = SizeTable {
                     h: data.len(),
-                    w: data
-                        .iter()
-                        .map(|item| item.len())
-                        .max()
-                        .unwrap_or(data[0].len()),
+                    w: data.iter().map(|item| item.len()).max().unwrap_or_default(),
                 };
-                Ok(Self {
+                let mut out = Self {
+                    sheets: single_sheet(&s),
                     file: s,
-                    selected_data: data,
-                    has_header: reader.has_headers(),
+                    selected_data: data.into(),
+                    has_header: dialect.has_header,
+                    dialect: Some(dialect),
+                    encoding: Some(encoding),
+                    warnings,
                     size,
                     ..Default::default()
-                })
+                };
+                out.loaded();
+                Ok(out)
             }
         }
     }
-    pub(crate) fn set_selected_data(&mut self, sheet: &str) -> DpdResult<()> {
+    /// tabel hasil konversi json / fixed width, baris pertama selalu header nama field
+    pub(crate) fn from_records(
+        file: String,
+        data: Vec<Vec<String>>,
+        warnings: Vec<LoadWarning>,
+    ) -> Self {
+        let mut out = Self {
+            sheets: single_sheet(&file),
+            file,
+            size: SizeTable {
+                h: data.len(),
+                w: data.first().map(|r| r.len()).unwrap_or_default(),
+            },
+            selected_data: data.into(),
+            has_header: true,
+            warnings,
+            ..Default::default()
+        };
+        out.loaded();
+        out
+    }
+
+    /// data dari clipboard, disimpan di memory dengan satu sheet `Clipboard`
+    pub fn from_clipboard(text: &str) -> DpdResult<Self> {
+        let pasted = parse_clipboard(text);
+        if pasted.data.is_empty() {
+            return Err(DpdError::Validation(
+                "Clipboard tidak berisi data tabel".to_owned(),
+            ));
+        }
+        let size = SizeTable {
+            h: pasted.data.len(),
+            w: pasted.data.iter().map(|r| r.len()).max().unwrap_or_default(),
+        };
+        let mut out = Self {
+            file: "clipboard".to_owned(),
+            sheets: vec!["Clipboard".to_owned()],
+            selected_data: pasted.data.into(),
+            has_header: pasted.has_header,
+            warnings: pasted.warnings,
+            size,
+            ..Default::default()
+        };
+        out.loaded();
+        Ok(out)
+    }
+
+    /// data lama tetap dipakai jika pembacaan gagal atau dibatalkan lewat `progress`
+    pub(crate) fn set_selected_data(&mut self, sheet: &str, progress: &Progress) -> DpdResult<()> {
+        if self.exl.is_some() {
+            let data = self.read_sheet(sheet, progress)?;
+            self.size = SizeTable {
+                h: data.len(),
+                w: data.iter().map(|r| r.len()).max().unwrap_or_default(),
+            };
+            self.selected_data = data.into();
+            self.loaded();
+        } else if self.db.is_some() {
+            let data = self.read_sheet(sheet, progress)?;
+            self.set_queried(data);
+        }
+        Ok(())
+    }
+
+    /// baca isi sheet / tabel tanpa mengganti data yang sedang dipilih,
+    /// konversi baris dilaporkan ke `progress` dan bisa dibatalkan
+    pub(crate) fn read_sheet(&mut self, sheet: &str, progress: &Progress) -> DpdResult<Vec<Vec<String>>> {
+        progress.check()?;
         if let Some(exl) = &mut self.exl {
             let data = match exl.worksheet_range(sheet) {
                 Some(ws) => Ok(ws?),
@@ -184,38 +818,465 @@ impl CmpData {
                     &sheet, &self.file
                 ))),
             }?;
-
-            self.selected_data = deserialize_data_excel(&data);
-            self.size = data.get_size().into();
-            Ok(())
+            deserialize_data_excel(&data, progress)
+        } else if let Some(db) = &self.db {
+            if !self.sheets.iter().any(|s| s == sheet) {
+                return Err(DpdError::Validation(format!(
+                    "Tidak ada nama tabel `{}` pada database `{}`",
+                    &sheet, &self.file
+                )));
+            }
+            let data = sqlite::load_table(db, sheet)?;
+            progress.check()?;
+            Ok(data)
         } else {
-            Ok(())
+            Ok(self.selected_data.to_vec())
         }
     }
 
+    /// data sheet lain dengan pengaturan header yang sama, tanpa file dan history
+    pub(crate) fn with_rows(&self, sheet: &str, rows: Table) -> Self {
+        let mut out = Self {
+            file: self.file.to_owned(),
+            sheets: vec![sheet.to_owned()],
+            size: SizeTable {
+                h: rows.len(),
+                w: rows.iter().map(|r| r.len()).max().unwrap_or_default(),
+            },
+            selected_data: rows,
+            has_header: self.has_header,
+            ..Default::default()
+        };
+        out.loaded();
+        out
+    }
+
+    /// ganti data dengan hasil query sql, hanya untuk input sqlite.
+    /// data lama tetap dipakai jika query gagal atau dibatalkan lewat `progress`
+    pub fn load_query(&mut self, sql: &str, progress: &Progress) -> DpdResult<()> {
+        progress.check()?;
+        let db = self.db.as_ref().ok_or_else(|| {
+            DpdError::Validation("Query hanya bisa dijalankan pada database sqlite".to_owned())
+        })?;
+        let data = sqlite::load_query(db, sql)?;
+        progress.check()?;
+        self.set_queried(data);
+        Ok(())
+    }
+
+    /// hasil query sqlite selalu diawali baris nama kolom
+    fn set_queried(&mut self, data: Vec<Vec<String>>) {
+        self.size = SizeTable {
+            h: data.len(),
+            w: data.first().map(|r| r.len()).unwrap_or_default(),
+        };
+        self.selected_data = data.into();
+        self.has_header = true;
+        self.loaded();
+    }
+
+    /// input yang memiliki lebih dari satu sheet / tabel untuk dipilih
     #[inline]
-    pub fn filter(&mut self, row: Option<usize>) -> DpdResult<()> {
-        self.is_filtered = true;
-        if let Some(r) = row {
-            self.selected_data.filter_col(r)
-        } else {
-            self.selected_data.filter_col(self.size.w)
+    pub fn is_workbook(&self) -> bool {
+        self.exl.is_some() || self.db.is_some()
+    }
+
+    /// data baru dimuat, menjadi data asli untuk history transform
+    fn loaded(&mut self) {
+        self.original = self.selected_data.clone();
+        self.history.clear();
+        self.redo.clear();
+        self.is_filtered = false;
+        self.changed();
+    }
+
+    /// nomor versi isi tabel, berbeda setiap kali data atau header berubah
+    /// (juga antar `CmpData` yang berbeda), dipakai sebagai kunci cache tampilan
+    #[inline]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn changed(&mut self) {
+        static GENERATION: AtomicU64 = AtomicU64::new(1);
+        self.generation = GENERATION.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// history diulang dari data asli, baris header tidak ikut di-sort / filter
+    pub fn set_has_header(&mut self, has_header: bool) -> DpdResult<()> {
+        if self.has_header != has_header {
+            self.has_header = has_header;
+            self.replay()?;
         }
+        Ok(())
+    }
+
+    fn update_size(&mut self) {
+        self.size.h = self.selected_data.len();
+        self.size.w = self.selected_data.iter().map(|r| r.len()).max().unwrap_or(0);
+        self.is_filtered = self
+            .history
+            .iter()
+            .any(|t| matches!(t, Transform::Filter(_)));
+        self.changed();
+    }
+
+    /// jalankan transform pada tabel dan catat ke history, history redo dibuang
+    pub fn apply(&mut self, transform: Transform) -> DpdResult<()> {
+        transform.apply(Arc::make_mut(&mut self.selected_data), usize::from(self.has_header))?;
+        self.history.push(transform);
+        self.redo.clear();
+        self.update_size();
+        Ok(())
+    }
+
+    /// bangun ulang tabel dari data asli dengan mengulang seluruh history
+    fn replay(&mut self) -> DpdResult<()> {
+        self.selected_data = self.original.clone();
+        let skip = usize::from(self.has_header);
+        for transform in self.history.iter() {
+            transform.apply(Arc::make_mut(&mut self.selected_data), skip)?;
+        }
+        self.update_size();
+        Ok(())
+    }
+
+    pub fn undo(&mut self) -> DpdResult<()> {
+        if let Some(transform) = self.history.pop() {
+            self.redo.push(transform);
+            self.replay()?;
+        }
+        Ok(())
+    }
+
+    pub fn redo(&mut self) -> DpdResult<()> {
+        if let Some(transform) = self.redo.pop() {
+            transform.apply(Arc::make_mut(&mut self.selected_data), usize::from(self.has_header))?;
+            self.history.push(transform);
+            self.update_size();
+        }
+        Ok(())
+    }
+
+    /// kembali ke data asli, seluruh history dibuang
+    pub fn reset(&mut self) -> DpdResult<()> {
+        self.history.clear();
+        self.redo.clear();
+        self.replay()
+    }
+
+    #[inline]
+    pub fn can_undo(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    #[inline]
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
     }
 
     #[inline]
-    pub fn sort(&mut self, row: Option<usize>) -> DpdResult<()> {
-        self.selected_data.sort_by_col(row.unwrap_or(self.size.w))
+    pub fn history(&self) -> &[Transform] {
+        &self.history
+    }
+
+    /// kunci sort yang sedang aktif (sort terakhir di history)
+    pub fn sort_keys(&self) -> &[SortKey] {
+        self.history
+            .iter()
+            .rev()
+            .find_map(|t| match t {
+                Transform::Sort(keys) => Some(keys.as_slice()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// hanya menyisakan baris yang memenuhi `expr`, baris header tidak ikut difilter
+    #[inline]
+    pub fn filter(&mut self, expr: &FilterExpr) -> DpdResult<()> {
+        self.apply(Transform::Filter(expr.to_owned()))
+    }
+
+    /// nama kolom dari baris header, atau `Kolom N` jika tidak ada header
+    pub fn column_names(&self) -> Vec<String> {
+        let header = self.selected_data.first().filter(|_| self.has_header);
+        (0..self.size.w)
+            .map(|idx| {
+                header
+                    .and_then(|h| h.get(idx))
+                    .map(|h| h.trim().to_owned())
+                    .filter(|h| !h.is_empty())
+                    .unwrap_or_else(|| format!("Kolom {}", idx + 1))
+            })
+            .collect()
+    }
+
+    /// baris header tetap di posisi paling atas
+    #[inline]
+    pub fn sort(&mut self, keys: &[SortKey]) -> DpdResult<()> {
+        self.apply(Transform::Sort(keys.to_owned()))
     }
 
     #[inline]
     pub fn close(&mut self) {
         self.file.clear();
         self.exl = None;
+        self.db = None;
         self.sheets.clear();
-        self.selected_data.clear();
+        self.selected_data = Default::default();
         self.size = SizeTable::default();
         self.has_header = false;
         self.is_filtered = false;
+        self.dialect = None;
+        self.encoding = None;
+        self.warnings.clear();
+        self.layout = None;
+        self.original = Default::default();
+        self.history.clear();
+        self.redo.clear();
+        self.changed();
+    }
+}
+
+/// file tanpa sheet diperlakukan seperti workbook dengan satu sheet bernama file tersebut
+fn single_sheet(file: &str) -> Vec<String> {
+    vec![Path::new(file)
+        .file_stem()
+        .and_then(|n| n.to_str())
+        .unwrap_or("data")
+        .to_owned()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(data: &[&str]) -> Vec<Vec<String>> {
+        data.iter()
+            .map(|r| r.split(',').map(str::to_owned).collect())
+            .collect()
+    }
+
+    fn side<'a>(rows: &'a [Vec<String>], file: &'a str, sheet: &'a str) -> Side<'a> {
+        Side { rows, file, sheet }
+    }
+
+    fn compare(src: &[&str], target: &[&str]) -> Vec<CmpRslt> {
+        let (src, target) = (rows(src), rows(target));
+        Comparison::run(
+            CmpOption::default(),
+            side(&src, "src", "Sheet1"),
+            side(&target, "target", "Sheet1"),
+            &Progress::default(),
+        )
+        .unwrap()
+        .0
+    }
+
+    #[test]
+    fn similar_rows_are_paired_as_modified() {
+        let out = compare(
+            &["Ani,10,Solo", "Budi,20,Jakarta", "Citra,30,Bandung"],
+            &["Ani,10,Solo", "Budi,21,Jakarta", "Citra,30,Bandung"],
+        );
+        assert_eq!(out.len(), 2);
+        assert!(out.iter().all(CmpRslt::is_modified));
+        assert_eq!(out[0].pair.as_deref(), Some(&rows(&["Budi,21,Jakarta"])[0][..]));
+    }
+
+    #[test]
+    fn unrelated_rows_stay_deleted_and_inserted() {
+        let out = compare(
+            &["Ani,10,Solo", "Dodi,40,Medan", "Citra,30,Bandung"],
+            &["Ani,10,Solo", "Eka,50,Bali", "Citra,30,Bandung"],
+        );
+        assert_eq!(out.len(), 2);
+        assert!(out.iter().all(|r| !r.is_modified()));
+        assert_eq!(out[0].tag_text(), "(-)DELETED");
+        assert_eq!(out[1].tag_text(), "(+)INSERTED");
+    }
+
+    #[test]
+    fn pairing_skips_unrelated_rows_in_the_same_block() {
+        let out = compare(
+            &["Ani,10,Solo", "Budi,20,Jakarta", "Zed,1,X"],
+            &["Eka,50,Bali", "Ani,11,Solo", "Budi,20,Jakarta2", "Zed,1,X"],
+        );
+        let modified = out
+            .iter()
+            .filter(|r| r.is_modified() && r.issrc)
+            .map(|r| r.index)
+            .collect::<Vec<_>>();
+        assert_eq!(modified, vec![0, 1]);
+        let inserted = out
+            .iter()
+            .filter(|r| !r.issrc && !r.is_modified())
+            .map(|r| r.index)
+            .collect::<Vec<_>>();
+        assert_eq!(inserted, vec![0]);
+    }
+
+    #[test]
+    fn each_side_keeps_its_own_sheet_name() {
+        let (src, target) = (rows(&["Ani,10", "Budi,20"]), rows(&["Ani,10", "Eka,90"]));
+        let out = Comparison::run(
+            CmpOption::default(),
+            side(&src, "a.xlsx", "Data 2021"),
+            side(&target, "b.xlsx", "Data 2022"),
+            &Progress::default(),
+        )
+        .unwrap()
+        .0;
+        for item in out.iter() {
+            let (file, sheet) = if item.issrc {
+                ("a.xlsx", "Data 2021")
+            } else {
+                ("b.xlsx", "Data 2022")
+            };
+            assert_eq!((item.file.as_str(), item.sheet.as_str()), (file, sheet));
+        }
+        assert_eq!(out.len(), 2);
+    }
+
+    #[test]
+    fn generation_changes_with_header_and_data() {
+        let mut data = CmpData::default().with_rows("s", rows(&["a,b", "1,2"]).into());
+        let start = data.generation();
+        data.set_has_header(true).unwrap();
+        let header = data.generation();
+        assert_ne!(start, header);
+        data.set_has_header(true).unwrap();
+        assert_eq!(data.generation(), header);
+        data.reset().unwrap();
+        assert_ne!(data.generation(), header);
+    }
+
+    fn diff(old: &[u64], new: &[u64], fast_path: bool) -> (RowDiff, DiffStats) {
+        let option = CmpOption {
+            fast_path,
+            ..Default::default()
+        };
+        let mut stats = DiffStats::default();
+        let diff = diff_fingerprints(&option, old, new, &mut stats, &Progress::default()).unwrap();
+        (diff, stats)
+    }
+
+    /// ops harus berurutan di kedua tabel, bagian `Equal` dan celah diantara ops
+    /// (prefix, suffix, anchor yang tidak ditulis sebagai op) harus identik
+    fn assert_valid_ops(ops: &[DiffOp], old: &[u64], new: &[u64]) {
+        let (mut o, mut n) = (0, 0);
+        let ends = ops
+            .iter()
+            .map(|op| op.as_tag_tuple())
+            .chain(std::iter::once((DiffTag::Equal, old.len()..old.len(), new.len()..new.len())));
+        for (tag, old_range, new_range) in ends {
+            assert_eq!(
+                old[o..old_range.start],
+                new[n..new_range.start],
+                "{:?}",
+                ops
+            );
+            if tag == DiffTag::Equal {
+                assert_eq!(old[old_range.clone()], new[new_range.clone()]);
+            }
+            (o, n) = (old_range.end, new_range.end);
+        }
+    }
+
+    fn assert_fast_matches_full(old: &[u64], new: &[u64]) -> DiffStats {
+        let (fast, stats) = diff(old, new, true);
+        let (full, _) = diff(old, new, false);
+        assert_valid_ops(&fast.ops, old, new);
+        assert_eq!(fast.changed_rows(), full.changed_rows());
+        assert_eq!(fast.moves, full.moves);
+        stats
+    }
+
+    #[test]
+    fn fast_path_trims_common_prefix_and_suffix() {
+        let old = [1, 2, 3, 4, 5, 6, 7];
+        let new = [1, 2, 3, 40, 5, 6, 7];
+        let stats = assert_fast_matches_full(&old, &new);
+        assert_eq!((stats.prefix, stats.suffix), (3, 3));
+        assert_eq!(stats.diffed, (1, 1));
+        assert_fast_matches_full(&old, &old);
+        assert_fast_matches_full(&old, &[]);
+        assert_fast_matches_full(&[], &new);
+    }
+
+    #[test]
+    fn fast_path_with_duplicate_rows() {
+        let old = [9, 1, 1, 2, 1, 1, 3, 9];
+        let new = [9, 1, 2, 1, 1, 1, 4, 3, 9];
+        let (fast, stats) = diff(&old, &new, true);
+        let (full, _) = diff(&old, &new, false);
+        assert_valid_ops(&fast.ops, &old, &new);
+        // baris yang muncul lebih dari sekali tidak pernah jadi anchor
+        assert_eq!(stats.anchored, 1);
+        let count = |d: &RowDiff| {
+            let (o, n) = d.changed_rows();
+            o.len() + n.len()
+        };
+        assert_eq!(count(&fast), count(&full));
+    }
+
+    #[test]
+    fn fast_path_without_anchors_diffs_the_whole_middle() {
+        let old = [0, 1, 1, 2, 2, 0];
+        let new = [0, 2, 2, 1, 1, 0];
+        let stats = assert_fast_matches_full(&old, &new);
+        assert_eq!(stats.anchored, 0);
+        assert_eq!(stats.diffed, (4, 4));
+    }
+
+    #[test]
+    fn fast_path_anchors_split_the_diff() {
+        let old = [1, 2, 3, 4, 5, 6];
+        let new = [1, 20, 3, 4, 50, 6];
+        let stats = assert_fast_matches_full(&old, &new);
+        assert_eq!(stats.anchored, 2);
+        assert_eq!(stats.diffed, (2, 2));
+    }
+
+    #[test]
+    fn moved_rows_are_found() {
+        let old = [1, 2, 3, 4];
+        let new = [2, 3, 4, 1];
+        let ops = capture_diff_slices(Algorithm::Myers, &old, &new);
+        assert_eq!(find_moves(&ops, &old, &new), HashMap::from([(0, 3)]));
+    }
+
+    #[test]
+    fn duplicate_moves_are_paired_in_order() {
+        let old = [7, 7, 1, 2, 3];
+        let new = [1, 2, 3, 7, 7];
+        let ops = capture_diff_slices(Algorithm::Myers, &old, &new);
+        assert_eq!(find_moves(&ops, &old, &new), HashMap::from([(0, 3), (1, 4)]));
+    }
+
+    #[test]
+    fn changed_rows_are_not_moves() {
+        let old = [1, 2, 3];
+        let new = [1, 4, 3];
+        let ops = capture_diff_slices(Algorithm::Myers, &old, &new);
+        assert!(find_moves(&ops, &old, &new).is_empty());
+    }
+
+    #[test]
+    fn header_toggle_replays_history() {
+        let mut data = CmpData::default().with_rows("s", rows(&["b", "c", "a"]).into());
+        data.sort(&[SortKey {
+            col: 0,
+            descending: false,
+        }])
+        .unwrap();
+        assert_eq!(*data.selected_data, rows(&["a", "b", "c"]));
+        data.set_has_header(true).unwrap();
+        assert_eq!(*data.selected_data, rows(&["b", "a", "c"]));
+        data.undo().unwrap();
+        assert_eq!(*data.selected_data, rows(&["b", "c", "a"]));
+        data.redo().unwrap();
+        assert_eq!(*data.selected_data, rows(&["b", "a", "c"]));
     }
 }
@@ -2,13 +2,21 @@ use std::path::Path;
 
 use calamine::{DataType, Range};
 
-use super::errors::{DpdError, DpdResult};
+use super::{
+    cell::excel_serial_to_string,
+    compares::{Progress, PROGRESS_CHUNK},
+    errors::{DpdError, DpdResult, LoadWarning},
+    format::{sniff, ExcelFormat, FileFormat},
+    schema::SchemaDiff,
+    CmpRslt,
+};
 
 #[allow(unused)]
 pub(crate) fn convert_csv_to_excel<P>(
     csv_data: Vec<Vec<String>>,
     excel_path: P,
     sheets_name: String,
+    schema: &SchemaDiff,
 ) -> Result<(), DpdError>
 where
     P: AsRef<Path>,
@@ -32,48 +40,292 @@ where
         Ok(())
     })?;
 
+    if !schema.is_empty() {
+        let mut sheet = wb.create_sheet("SCHEMA");
+        wb.write_sheet(&mut sheet, |sw| {
+            for change in schema.to_rows() {
+                let mut row = simple_excel_writer::Row::new();
+                for field in change {
+                    row.add_cell(field);
+                }
+                sw.append_row(row)?;
+            }
+            Ok(())
+        })?;
+    }
+
     wb.close()?;
     Ok(())
 }
 
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn write_html_table(buf: &mut String, title: &str, items: &[CmpRslt]) -> std::fmt::Result {
+    use similar::ChangeTag;
+    use std::fmt::Write;
+
+    writeln!(buf, "<h2>{}</h2>", escape_html(title))?;
+    writeln!(
+        buf,
+        "<table><tr><th>Tag</th><th>Nomor</th><th>File</th><th>Sheet</th><th>Data</th></tr>"
+    )?;
+    for item in items {
+        write!(
+            buf,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td>",
+            escape_html(&item.tag_text()),
+            item.number(),
+            escape_html(&item.file),
+            escape_html(&item.sheet)
+        )?;
+        match item.inline_diff() {
+            Some(cells) => {
+                for cell in cells {
+                    write!(buf, "<td>")?;
+                    for frag in cell {
+                        let text = escape_html(&frag.text);
+                        match frag.tag {
+                            ChangeTag::Equal => write!(buf, "{}", text)?,
+                            ChangeTag::Delete => write!(buf, "<del>{}</del>", text)?,
+                            ChangeTag::Insert => write!(buf, "<ins>{}</ins>", text)?,
+                        }
+                    }
+                    write!(buf, "</td>")?;
+                }
+            }
+            None => {
+                for cell in item.data.iter() {
+                    write!(buf, "<td>{}</td>", escape_html(cell))?;
+                }
+            }
+        }
+        writeln!(buf, "</tr>")?;
+    }
+    writeln!(buf, "</table>")
+}
+
 #[allow(unused)]
-pub(crate) fn deserialize_data_excel(range: &Range<DataType>) -> Vec<Vec<String>> {
+pub(crate) fn convert_to_html<P>(
+    src: &[CmpRslt],
+    tgt: &[CmpRslt],
+    schema: &SchemaDiff,
+    html_path: P,
+) -> DpdResult<()>
+where
+    P: AsRef<Path>,
+{
+    let mut buf = String::new();
+    buf.push_str(concat!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>OUTPUT DIFF</title>\n",
+        "<style>body{font-family:sans-serif}table{border-collapse:collapse;margin-bottom:2em}",
+        "td,th{border:1px solid #999;padding:2px 6px}",
+        "del{color:#c00;background:#fdd;text-decoration:line-through}",
+        "ins{color:#070;background:#dfd;text-decoration:none}</style>\n",
+        "</head><body>\n"
+    ));
+    if !schema.is_empty() {
+        use std::fmt::Write;
+        writeln!(buf, "<h2>SCHEMA</h2>\n<ul>")?;
+        for change in schema.0.iter() {
+            writeln!(
+                buf,
+                "<li><b>{}</b> {}</li>",
+                change.label(),
+                escape_html(&change.description())
+            )?;
+        }
+        writeln!(buf, "</ul>")?;
+    }
+    write_html_table(&mut buf, "SUMBER", src)?;
+    write_html_table(&mut buf, "TARGET", tgt)?;
+    buf.push_str("</body></html>\n");
+    std::fs::write(html_path, buf)?;
+    Ok(())
+}
+
+/// baca record berikutnya ke `record`. jika dialect tidak `flexible`, record dengan jumlah
+/// kolom berbeda dari baris pertama dilewati dan dicatat ke `warnings`;
+/// `false` jika file habis atau pembacaan dihentikan karena error
+pub(crate) fn read_record<R: std::io::Read>(
+    reader: &mut csv::Reader<R>,
+    record: &mut csv::ByteRecord,
+    warnings: &mut Vec<LoadWarning>,
+) -> bool {
+    loop {
+        let line = reader.position().line();
+        match reader.read_byte_record(record) {
+            Ok(more) => return more,
+            Err(e) => match e.kind() {
+                csv::ErrorKind::UnequalLengths { expected_len, len, pos } => {
+                    warnings.push(LoadWarning {
+                        line: pos.as_ref().map(|p| p.line()).unwrap_or(line),
+                        message: format!(
+                            "jumlah kolom {} berbeda dari baris pertama ({} kolom), baris dilewati",
+                            len, expected_len
+                        ),
+                    });
+                }
+                _ => {
+                    warnings.push(LoadWarning {
+                        line,
+                        message: format!("{}, pembacaan file dihentikan", e),
+                    });
+                    return false;
+                }
+            },
+        }
+    }
+}
+
+/// warning untuk record yang berisi text non UTF-8, text tersebut dikonversi lossy
+pub(crate) fn check_record(record: &csv::ByteRecord) -> Option<LoadWarning> {
+    record
+        .iter()
+        .any(|field| std::str::from_utf8(field).is_err())
+        .then(|| LoadWarning {
+            line: record.position().map(|p| p.line()).unwrap_or_default(),
+            message: "text bukan UTF-8 yang valid, karakter yang rusak diganti `\u{FFFD}`"
+                .to_owned(),
+        })
+}
+
+/// baca seluruh record csv, record yang bermasalah dicatat sebagai warning beserta nomor barisnya.
+/// text non UTF-8 tetap dimuat (dikonversi lossy), baris dengan jumlah kolom berbeda
+/// hanya dilewati jika dialect tidak `flexible`
+pub(crate) fn deserialize_data_csv<R: std::io::Read>(
+    reader: &mut csv::Reader<R>,
+) -> (Vec<Vec<String>>, Vec<LoadWarning>) {
+    let mut out = Vec::new();
+    let mut warnings = Vec::new();
+    let mut record = csv::ByteRecord::new();
+    while read_record(reader, &mut record, &mut warnings) {
+        warnings.extend(check_record(&record));
+        out.push(
+            record
+                .iter()
+                .map(|field| String::from_utf8_lossy(field).into_owned())
+                .collect(),
+        );
+    }
+    if out.is_empty() && warnings.is_empty() {
+        warnings.push(LoadWarning {
+            line: 0,
+            message: "file kosong, tidak ada baris yang dimuat".to_owned(),
+        });
+    }
+    (out, warnings)
+}
+
+#[allow(unused)]
+/// baris yang dikonversi dilaporkan ke `progress`, pembacaan berhenti jika dibatalkan
+pub(crate) fn deserialize_data_excel(
+    range: &Range<DataType>,
+    progress: &Progress,
+) -> DpdResult<Vec<Vec<String>>> {
     // let mut dest = String::new();
     let mut out = Vec::new();
     out.reserve(range.get_size().0);
-    for r in range.rows() {
+    progress.add_total(range.height());
+    for (idx, r) in range.rows().enumerate() {
+        if idx % PROGRESS_CHUNK == 0 {
+            progress.check()?;
+            progress.advance(PROGRESS_CHUNK.min(range.height() - idx));
+        }
         let mut row = Vec::new();
         row.reserve(r.len());
         for c in r.iter() {
             match *c {
-                DataType::Empty => (),
+                // cell kosong tetap diisi supaya posisi kolom tidak bergeser
+                DataType::Empty => row.push(String::new()),
                 DataType::String(ref s) => row.push(s.to_owned()),
-                DataType::Float(ref f) | DataType::DateTime(ref f) => row.push(f.to_string()),
+                DataType::Float(ref f) => row.push(f.to_string()),
+                DataType::DateTime(ref f) => row.push(excel_serial_to_string(*f)),
                 DataType::Int(ref i) => row.push(i.to_string()),
                 DataType::Error(ref e) => row.push(e.to_string()),
                 DataType::Bool(ref b) => row.push(b.to_string()),
             };
         }
-        if !row.is_empty() {
+        if row.iter().any(|c| !c.is_empty()) {
             out.push(row);
         }
     }
-    out
+    Ok(out)
 }
 
 pub enum TypeTable {
-    Excel(String),
+    Excel(String, ExcelFormat),
     Csv(String),
+    Sqlite(String),
+    Json(String),
+    JsonLines(String),
 }
 
+/// format ditentukan dari isi file, sehingga file dengan extension yang salah tetap bisa dibuka
 #[inline]
 pub fn validate<P: AsRef<Path>>(f: P) -> DpdResult<TypeTable> {
     let file = f.as_ref();
-    match file.extension().and_then(|s| s.to_str()) {
-        Some("xlsx") | Some("xlsm") | Some("xlsb") | Some("xls") => Ok(TypeTable::Excel(
-            file.to_str().unwrap_or_default().to_owned(),
-        )),
-        Some("csv") => Ok(TypeTable::Csv(file.to_str().unwrap_or_default().to_owned())),
-        _ => Err(DpdError::Validation("Expecting an excel file".to_owned())),
+    let name = file.to_str().unwrap_or_default().to_owned();
+    match sniff(file)? {
+        FileFormat::Excel(format) => Ok(TypeTable::Excel(name, format)),
+        FileFormat::Sqlite => Ok(TypeTable::Sqlite(name)),
+        FileFormat::Json => Ok(TypeTable::Json(name)),
+        FileFormat::JsonLines => Ok(TypeTable::JsonLines(name)),
+        FileFormat::Text => Ok(TypeTable::Csv(name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(data: &[u8], flexible: bool) -> (Vec<Vec<String>>, Vec<LoadWarning>) {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(flexible)
+            .from_reader(data);
+        deserialize_data_csv(&mut reader)
+    }
+
+    #[test]
+    fn uneven_and_invalid_utf8_records_are_kept_with_warnings() {
+        let (rows, warnings) = read(b"a,b\n1,2\n3,4,5\n\xff,6\n", true);
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[2], vec!["3", "4", "5"]);
+        assert_eq!(rows[3][0], "\u{FFFD}");
+        assert_eq!(warnings.iter().map(|w| w.line).collect::<Vec<_>>(), vec![4]);
+    }
+
+    #[test]
+    fn strict_dialect_skips_uneven_records() {
+        let (rows, warnings) = read(b"a,b\n1,2\n3,4,5\n6\n7,8\n", false);
+        assert_eq!(rows, vec![vec!["a", "b"], vec!["1", "2"], vec!["7", "8"]]);
+        assert_eq!(
+            warnings.iter().map(|w| w.line).collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+    }
+
+    #[test]
+    fn html_export_numbers_rows_from_one() {
+        let item = CmpRslt {
+            index: 2,
+            moved: Some((2, 5)),
+            ..Default::default()
+        };
+        let mut buf = String::new();
+        write_html_table(&mut buf, "SUMBER", &[item]).unwrap();
+        assert!(buf.contains("<td>(&gt;)MOVED 3 -&gt; 6</td><td>3</td>"), "{}", buf);
     }
 }
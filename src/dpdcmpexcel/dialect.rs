@@ -0,0 +1,302 @@
+use std::{collections::HashMap, io::Read, path::Path};
+
+use encoding_rs::Encoding;
+
+use super::{
+    cell::CellType,
+    encoding::{detect_file, open_decoded},
+    errors::DpdResult,
+    fixedwidth::FixedLayout,
+};
+
+/// jumlah byte awal file yang dibaca untuk mendeteksi dialect
+pub(crate) const SNIFF_SIZE: u64 = 64 * 1024;
+const SNIFF_LINES: usize = 50;
+const DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvDialect {
+    pub delimiter: u8,
+    pub quote: u8,
+    pub escape: Option<u8>,
+    pub has_header: bool,
+    pub comment: Option<u8>,
+    pub flexible: bool,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+            escape: None,
+            has_header: true,
+            comment: None,
+            flexible: true,
+        }
+    }
+}
+
+/// bagian dialect yang ditentukan pengguna, `None` berarti memakai hasil deteksi
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DialectOverride {
+    pub delimiter: Option<u8>,
+    pub quote: Option<u8>,
+    pub escape: Option<u8>,
+    pub has_header: Option<bool>,
+    pub comment: Option<u8>,
+    pub flexible: Option<bool>,
+}
+
+impl DialectOverride {
+    pub fn apply(&self, d: CsvDialect) -> CsvDialect {
+        CsvDialect {
+            delimiter: self.delimiter.unwrap_or(d.delimiter),
+            quote: self.quote.unwrap_or(d.quote),
+            escape: self.escape.or(d.escape),
+            has_header: self.has_header.unwrap_or(d.has_header),
+            comment: self.comment.or(d.comment),
+            flexible: self.flexible.unwrap_or(d.flexible),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CsvOption {
+    /// `None` berarti dialect dideteksi otomatis dari isi file
+    pub dialect: Option<CsvDialect>,
+    /// ditimpakan ke dialect hasil deteksi, diabaikan jika `dialect` diisi
+    pub overrides: DialectOverride,
+    /// `None` berarti encoding dideteksi otomatis dari BOM / isi file
+    pub encoding: Option<&'static Encoding>,
+    /// jika diisi, file text dibaca sebagai fixed width dengan layout ini, bukan csv
+    pub layout: Option<FixedLayout>,
+}
+
+impl CsvOption {
+    pub fn resolve_encoding<P: AsRef<Path>>(&self, path: P) -> DpdResult<&'static Encoding> {
+        match self.encoding {
+            Some(e) => Ok(e),
+            None => detect_file(path),
+        }
+    }
+
+    /// dialect dari opsi, atau dideteksi dari isi file (`.tsv` selalu dipisah tab)
+    /// lalu ditimpa oleh `overrides`
+    pub fn resolve_dialect<P: AsRef<Path>>(
+        &self,
+        path: P,
+        encoding: &'static Encoding,
+    ) -> DpdResult<CsvDialect> {
+        if let Some(d) = self.dialect {
+            return Ok(d);
+        }
+        let mut d = CsvDialect::sniff_file(path.as_ref(), encoding)?;
+        let tsv = path
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("tsv"));
+        if tsv {
+            d.delimiter = b'\t';
+        }
+        Ok(self.overrides.apply(d))
+    }
+}
+
+impl CsvDialect {
+    /// header tidak pernah dilewati oleh reader, baris header tetap masuk ke data
+    pub fn reader_builder(&self) -> csv::ReaderBuilder {
+        let mut builder = csv::ReaderBuilder::new();
+        builder
+            .has_headers(false)
+            .delimiter(self.delimiter)
+            .quote(self.quote)
+            .escape(self.escape)
+            .double_quote(self.escape.is_none())
+            .comment(self.comment)
+            .flexible(self.flexible);
+        builder
+    }
+
+    pub fn sniff_file<P: AsRef<Path>>(path: P, encoding: &'static Encoding) -> DpdResult<Self> {
+        let mut sample = vec![];
+        open_decoded(path, encoding)?
+            .take(SNIFF_SIZE)
+            .read_to_end(&mut sample)?;
+        Ok(Self::sniff(&sample))
+    }
+
+    /// deteksi delimiter, quote dan header dari potongan awal file
+    pub fn sniff(sample: &[u8]) -> Self {
+        let mut out = Self::default();
+        let lines = sample
+            .split(|&b| b == b'\n')
+            .map(|l| l.strip_suffix(b"\r").unwrap_or(l))
+            .filter(|l| !l.iter().all(u8::is_ascii_whitespace))
+            .take(SNIFF_LINES)
+            .collect::<Vec<_>>();
+        // baris terakhir bisa terpotong oleh batas SNIFF_SIZE
+        let lines = if lines.len() > 1 && sample.len() as u64 >= SNIFF_SIZE {
+            &lines[..lines.len() - 1]
+        } else {
+            &lines[..]
+        };
+        if lines.is_empty() {
+            return out;
+        }
+
+        out.quote = sniff_quote(lines);
+        // delimiter terbaik: jumlah kemunculan per baris paling konsisten, lalu paling banyak
+        let mut best = (0usize, 0usize);
+        for delim in DELIMITERS {
+            let counts = lines
+                .iter()
+                .map(|l| count_outside_quotes(l, delim, out.quote))
+                .collect::<Vec<_>>();
+            let modal = mode(&counts);
+            if modal == 0 {
+                continue;
+            }
+            let consistent = counts.iter().filter(|&&c| c == modal).count();
+            if (consistent, modal) > best {
+                best = (consistent, modal);
+                out.delimiter = delim;
+            }
+        }
+        out.flexible = true;
+        out.has_header = sniff_header(&out, sample);
+        out
+    }
+}
+
+fn count_outside_quotes(line: &[u8], delim: u8, quote: u8) -> usize {
+    let mut quoted = false;
+    let mut count = 0;
+    for &b in line {
+        if b == quote {
+            quoted = !quoted;
+        } else if b == delim && !quoted {
+            count += 1;
+        }
+    }
+    count
+}
+
+fn mode(counts: &[usize]) -> usize {
+    let mut freq: HashMap<usize, usize> = HashMap::new();
+    for &c in counts {
+        *freq.entry(c).or_default() += 1;
+    }
+    freq.into_iter()
+        .max_by_key(|&(count, n)| (n, count))
+        .map(|(count, _)| count)
+        .unwrap_or_default()
+}
+
+/// quote yang lebih sering muncul tepat setelah awal baris / delimiter
+fn sniff_quote(lines: &[&[u8]]) -> u8 {
+    let opening = |quote: u8| {
+        lines
+            .iter()
+            .flat_map(|l| {
+                l.iter()
+                    .enumerate()
+                    .filter(move |&(i, &b)| b == quote && (i == 0 || DELIMITERS.contains(&l[i - 1])))
+            })
+            .count()
+    };
+    if opening(b'\'') > opening(b'"') {
+        b'\''
+    } else {
+        b'"'
+    }
+}
+
+/// baris pertama dianggap header jika seluruhnya text tidak kosong,
+/// sementara ada kolom yang pada baris berikutnya berisi angka / tanggal
+fn sniff_header(dialect: &CsvDialect, sample: &[u8]) -> bool {
+    let mut reader = dialect.reader_builder().from_reader(sample);
+    let rows = reader
+        .records()
+        .take(SNIFF_LINES)
+        .filter_map(|r| r.ok())
+        .collect::<Vec<_>>();
+    let (first, rest) = match rows.split_first() {
+        Some(split) => split,
+        None => return false,
+    };
+    if first
+        .iter()
+        .any(|c| !matches!(CellType::infer(c), CellType::Text))
+    {
+        return false;
+    }
+    if rest.is_empty() {
+        return true;
+    }
+    (0..first.len()).any(|col| {
+        matches!(
+            CellType::infer_column(rest.iter().map(|r| r.get(col).unwrap_or_default())),
+            CellType::Number | CellType::Date
+        )
+    }) || first.iter().all(|c| rest.iter().all(|r| !r.iter().any(|v| v == c)))
+}
+
+/// text dari input pengguna ke satu byte, `\t` atau `tab` untuk tab, kosong berarti `None`
+pub fn parse_dialect_char(s: &str) -> Option<u8> {
+    match s {
+        "" => None,
+        "\\t" | "tab" | "TAB" => Some(b'\t'),
+        s if s.len() == 1 && s.is_ascii() => Some(s.as_bytes()[0]),
+        _ => None,
+    }
+}
+
+pub fn display_dialect_char(c: Option<u8>) -> String {
+    match c {
+        None => String::new(),
+        Some(b'\t') => "\\t".to_owned(),
+        Some(c) => (c as char).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_replace_only_the_given_fields() {
+        let sniffed = CsvDialect::sniff(b"nama;umur\nbudi;20\nsiti;30\n");
+        let d = DialectOverride {
+            quote: Some(b'\''),
+            has_header: Some(false),
+            ..Default::default()
+        }
+        .apply(sniffed);
+        assert_eq!(d.delimiter, b';');
+        assert_eq!(d.quote, b'\'');
+        assert!(!d.has_header);
+        assert_eq!(DialectOverride::default().apply(sniffed), sniffed);
+    }
+
+    #[test]
+    fn sniff_picks_the_consistent_delimiter() {
+        assert_eq!(CsvDialect::sniff(b"a;b;c\n1;2,5;3\n4;5,5;6\n").delimiter, b';');
+        assert_eq!(CsvDialect::sniff(b"a\tb\n1\t2\n").delimiter, b'\t');
+        assert_eq!(CsvDialect::sniff(b"a|b|c\n1|2|3\n").delimiter, b'|');
+    }
+
+    #[test]
+    fn sniff_ignores_delimiters_inside_quotes() {
+        let d = CsvDialect::sniff(b"nama;alamat\n'budi';'jl. a, no. 1'\n'siti';'jl. b, no. 2'\n");
+        assert_eq!(d.quote, b'\'');
+        assert_eq!(d.delimiter, b';');
+    }
+
+    #[test]
+    fn sniff_header_only_when_first_row_looks_like_names() {
+        assert!(CsvDialect::sniff(b"nama,umur\nbudi,20\nsiti,31\n").has_header);
+        assert!(!CsvDialect::sniff(b"budi,20\nsiti,31\n").has_header);
+    }
+}
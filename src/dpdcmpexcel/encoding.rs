@@ -0,0 +1,126 @@
+use std::{fs::File, io::Read, path::Path};
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use encoding_rs_io::{DecodeReaderBytes, DecodeReaderBytesBuilder};
+
+use super::{dialect::SNIFF_SIZE, errors::DpdResult};
+
+/// encoding yang bisa dipilih manual di GUI
+pub fn encodings() -> [&'static Encoding; 8] {
+    [
+        UTF_8,
+        UTF_16LE,
+        UTF_16BE,
+        encoding_rs::WINDOWS_1252,
+        encoding_rs::WINDOWS_1250,
+        encoding_rs::ISO_8859_15,
+        encoding_rs::IBM866,
+        encoding_rs::SHIFT_JIS,
+    ]
+}
+
+/// `utf-8`, `windows-1252`, `latin1`, `utf-16le` dst, mengikuti label WHATWG
+pub fn parse_encoding(label: &str) -> Option<&'static Encoding> {
+    Encoding::for_label(label.trim().as_bytes())
+}
+
+pub fn detect_file<P: AsRef<Path>>(path: P) -> DpdResult<&'static Encoding> {
+    let mut sample = vec![];
+    File::open(path)?
+        .take(SNIFF_SIZE)
+        .read_to_end(&mut sample)?;
+    Ok(detect(&sample))
+}
+
+/// deteksi encoding dari BOM, pola byte NUL (UTF-16 tanpa BOM),
+/// validasi UTF-8, lalu tebakan statistik untuk encoding lama
+pub fn detect(sample: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(sample) {
+        return encoding;
+    }
+    if let Some(encoding) = detect_utf16(sample) {
+        return encoding;
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => return UTF_8,
+        // karakter terakhir bisa terpotong oleh batas SNIFF_SIZE
+        Err(e) if e.error_len().is_none() => return UTF_8,
+        Err(_) => (),
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(sample, (sample.len() as u64) < SNIFF_SIZE);
+    detector.guess(None, true)
+}
+
+/// text latin dalam UTF-16 hampir selalu punya byte NUL di setiap posisi ganjil (LE) / genap (BE)
+fn detect_utf16(sample: &[u8]) -> Option<&'static Encoding> {
+    let pairs = sample.len() / 2;
+    if pairs < 2 {
+        return None;
+    }
+    let (mut even, mut odd) = (0usize, 0usize);
+    for pair in sample.chunks_exact(2) {
+        even += (pair[0] == 0) as usize;
+        odd += (pair[1] == 0) as usize;
+    }
+    if odd * 10 >= pairs * 4 && even * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even * 10 >= pairs * 4 && odd * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// reader yang mengubah isi file ke UTF-8, BOM dibuang.
+/// UTF-8 diteruskan apa adanya agar byte yang rusak tetap dilaporkan saat dimuat
+pub fn open_decoded<P: AsRef<Path>>(
+    path: P,
+    encoding: &'static Encoding,
+) -> DpdResult<DecodeReaderBytes<File, Vec<u8>>> {
+    Ok(DecodeReaderBytesBuilder::new()
+        .encoding(Some(encoding))
+        .bom_override(true)
+        .strip_bom(true)
+        .utf8_passthru(true)
+        .build(File::open(path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_bom_and_utf8() {
+        assert_eq!(detect(b"\xEF\xBB\xBFnama,umur\n"), UTF_8);
+        assert_eq!(detect(b"\xFF\xFEn\x00a\x00"), UTF_16LE);
+        assert_eq!(detect("nama,kota\nJosé,Bogotá\n".as_bytes()), UTF_8);
+    }
+
+    #[test]
+    fn detect_utf16_without_bom() {
+        let le = "nama,umur\nbudi,20\n"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+        let be = "nama,umur\nbudi,20\n"
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect::<Vec<_>>();
+        assert_eq!(detect(&le), UTF_16LE);
+        assert_eq!(detect(&be), UTF_16BE);
+    }
+
+    #[test]
+    fn utf8_cut_at_the_end_is_still_utf8() {
+        let text = "nama,kota\nJosé".as_bytes();
+        assert_eq!(detect(&text[..text.len() - 1]), UTF_8);
+    }
+
+    #[test]
+    fn legacy_bytes_fall_back_to_a_guess() {
+        let (bytes, _, _) = encoding_rs::WINDOWS_1252
+            .encode("nama,kota\nJosé,Bogotá\nFrançois,Besançon\nRenée,Orléans\n");
+        assert_ne!(detect(&bytes), UTF_8);
+    }
+}
@@ -12,10 +12,15 @@ pub enum DpdError {
     #[error("Processing csv error")]
     Csv(#[from] csv::Error),
 
+    #[error("Processing sqlite error: {0}")]
+    Sqlite(#[from] rusqlite::Error),
+
     #[error("Validation error: {0}")]
     Validation(String),
     #[error("Processing error: {0}")]
     Processing(String),
+    #[error("Proses dibatalkan")]
+    Cancelled,
 
     #[allow(unused)]
     #[error("Unkown Error")]
@@ -23,3 +28,20 @@ pub enum DpdError {
 }
 
 pub type DpdResult<T> = Result<T, DpdError>;
+
+/// masalah pada satu baris saat memuat file, baris tetap dimuat sebisanya
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadWarning {
+    pub line: u64,
+    pub message: String,
+}
+
+impl std::fmt::Display for LoadWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "baris {}: {}", self.line, self.message)
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use std::fmt;
+
+use regex::{Regex, RegexBuilder};
+
+use super::{
+    cell::parse_number,
+    errors::{DpdError, DpdResult},
+};
+
+#[derive(Debug, Clone)]
+pub enum FilterOp {
+    Equals(String),
+    Contains(String),
+    Regex(Regex),
+    Range { min: Option<f64>, max: Option<f64> },
+    Empty,
+    NotEmpty,
+}
+
+impl FilterOp {
+    pub fn matches(&self, cell: &str) -> bool {
+        match self {
+            FilterOp::Equals(v) => cell.trim() == v.trim(),
+            FilterOp::Contains(v) => cell.to_lowercase().contains(&v.to_lowercase()),
+            FilterOp::Regex(re) => re.is_match(cell),
+            FilterOp::Range { min, max } => match parse_number(cell) {
+                Some(n) => !min.is_some_and(|m| n < m) && !max.is_some_and(|m| n > m),
+                None => false,
+            },
+            FilterOp::Empty => cell.trim().is_empty(),
+            FilterOp::NotEmpty => !cell.trim().is_empty(),
+        }
+    }
+}
+
+impl fmt::Display for FilterOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FilterOp::Equals(v) => write!(f, "= {}", v),
+            FilterOp::Contains(v) => write!(f, "~ {}", v),
+            FilterOp::Regex(re) => write!(f, "=~ {}", re.as_str()),
+            FilterOp::Range { min, max } => {
+                write!(f, "in ")?;
+                if let Some(m) = min {
+                    write!(f, "{}", m)?;
+                }
+                write!(f, "..")?;
+                if let Some(m) = max {
+                    write!(f, "{}", m)?;
+                }
+                Ok(())
+            }
+            FilterOp::Empty => write!(f, "is empty"),
+            FilterOp::NotEmpty => write!(f, "not empty"),
+        }
+    }
+}
+
+/// satu kondisi terhadap isi kolom `col` (index dimulai dari 0)
+#[derive(Debug, Clone)]
+pub struct Condition {
+    pub col: usize,
+    pub op: FilterOp,
+}
+
+impl Condition {
+    #[inline]
+    pub fn matches(&self, row: &[String]) -> bool {
+        self.op.matches(row.get(self.col).map(String::as_str).unwrap_or_default())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Join {
+    #[default]
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone)]
+pub enum FilterExpr {
+    Cond(Condition),
+    All(Vec<FilterExpr>),
+    Any(Vec<FilterExpr>),
+}
+
+impl FilterExpr {
+    pub fn matches(&self, row: &[String]) -> bool {
+        match self {
+            FilterExpr::Cond(c) => c.matches(row),
+            FilterExpr::All(all) => all.iter().all(|e| e.matches(row)),
+            FilterExpr::Any(any) => any.iter().any(|e| e.matches(row)),
+        }
+    }
+
+    /// rangkaian kondisi `a AND b OR c AND d`, AND lebih diutamakan dari OR
+    /// sehingga hasilnya `(a AND b) OR (c AND d)`, `Join` kondisi pertama diabaikan
+    pub fn from_chain<I>(chain: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = (Join, Condition)>,
+    {
+        let mut groups: Vec<Vec<FilterExpr>> = vec![];
+        for (join, cond) in chain {
+            match groups.last_mut() {
+                Some(group) if join == Join::And => group.push(FilterExpr::Cond(cond)),
+                _ => groups.push(vec![FilterExpr::Cond(cond)]),
+            }
+        }
+        let mut groups = groups
+            .into_iter()
+            .map(|mut g| {
+                if g.len() == 1 {
+                    g.remove(0)
+                } else {
+                    FilterExpr::All(g)
+                }
+            })
+            .collect::<Vec<_>>();
+        match groups.len() {
+            0 => None,
+            1 => groups.pop(),
+            _ => Some(FilterExpr::Any(groups)),
+        }
+    }
+
+    /// parsing ekspresi dari text, contoh:
+    /// `Nama ~ budi && 3 in 10..20 || "Kode Sekolah" = 123 && Alamat not empty`
+    ///
+    /// kolom ditulis sebagai nomor (dimulai dari 1) atau nama header, operator yang didukung:
+    /// `=`, `~` (mengandung), `=~` (regex), `in min..max`, `is empty`, `not empty`.
+    /// nilai boleh diberi tanda kutip, misal `Nama ~ "a||b"`, `\"` untuk kutip di dalamnya
+    pub fn parse(expr: &str, header: Option<&[String]>) -> DpdResult<Self> {
+        let chain = split_clauses(expr)?
+            .into_iter()
+            .map(|(join, cond)| Ok((join, parse_condition(cond.trim(), header)?)))
+            .collect::<DpdResult<Vec<_>>>()?;
+        Self::from_chain(chain)
+            .ok_or_else(|| DpdError::Validation("Ekspresi filter kosong".to_owned()))
+    }
+}
+
+pub fn build_op(op: &str, value: &str) -> DpdResult<FilterOp> {
+    let value = value.trim();
+    match op {
+        "=" | "==" => Ok(FilterOp::Equals(value.to_owned())),
+        "~" => Ok(FilterOp::Contains(value.to_owned())),
+        "=~" => RegexBuilder::new(value)
+            .case_insensitive(true)
+            .build()
+            .map(FilterOp::Regex)
+            .map_err(|e| DpdError::Validation(format!("Regex `{}` tidak valid | {}", value, e))),
+        "in" => {
+            let (min, max) = value.split_once("..").ok_or_else(|| {
+                DpdError::Validation(format!("Range `{}` harus berbentuk `min..max`", value))
+            })?;
+            let num = |s: &str| -> DpdResult<Option<f64>> {
+                if s.trim().is_empty() {
+                    Ok(None)
+                } else {
+                    parse_number(s).map(Some).ok_or_else(|| {
+                        DpdError::Validation(format!("`{}` bukan angka pada range filter", s))
+                    })
+                }
+            };
+            Ok(FilterOp::Range {
+                min: num(min)?,
+                max: num(max)?,
+            })
+        }
+        "is" if value == "empty" => Ok(FilterOp::Empty),
+        "not" if value == "empty" => Ok(FilterOp::NotEmpty),
+        _ => Err(DpdError::Validation(format!(
+            "Operator filter `{} {}` tidak dikenali",
+            op, value
+        ))),
+    }
+}
+
+/// potong ekspresi pada `&&` / `||` yang berada di luar tanda kutip
+fn split_clauses(expr: &str) -> DpdResult<Vec<(Join, &str)>> {
+    let bytes = expr.as_bytes();
+    let mut out = vec![];
+    let (mut start, mut join, mut quoted) = (0, Join::And, false);
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if quoted => i += 1,
+            b'"' => quoted = !quoted,
+            b @ (b'&' | b'|') if !quoted && bytes.get(i + 1) == Some(&b) => {
+                out.push((join, &expr[start..i]));
+                join = if b == b'&' { Join::And } else { Join::Or };
+                i += 2;
+                start = i;
+                continue;
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    if quoted {
+        return Err(DpdError::Validation(format!(
+            "Tanda kutip tidak ditutup: `{}`",
+            expr
+        )));
+    }
+    out.push((join, &expr[start..]));
+    Ok(out)
+}
+
+/// nilai dalam tanda kutip dibuka, hanya `\"` yang di-unescape
+/// supaya escape regex seperti `\d` tetap utuh
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    match value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .filter(|_| value.len() >= 2)
+    {
+        Some(inner) => inner.replace("\\\"", "\""),
+        None => value.to_owned(),
+    }
+}
+
+fn parse_condition(cond: &str, header: Option<&[String]>) -> DpdResult<Condition> {
+    let (col, rest) = match cond.strip_prefix('"') {
+        Some(quoted) => quoted.split_once('"').ok_or_else(|| {
+            DpdError::Validation(format!("Tanda kutip kolom tidak ditutup: `{}`", cond))
+        })?,
+        None => cond
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| DpdError::Validation(format!("Kondisi filter tidak lengkap: `{}`", cond)))?,
+    };
+    let rest = rest.trim_start();
+    let (op, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+
+    let col = match col.parse::<usize>() {
+        Ok(n) if n > 0 => n - 1,
+        _ => header
+            .and_then(|h| h.iter().position(|c| c.trim().eq_ignore_ascii_case(col.trim())))
+            .ok_or_else(|| DpdError::Validation(format!("Kolom `{}` tidak ditemukan", col)))?,
+    };
+    Ok(Condition {
+        col,
+        op: build_op(op, &unquote(value))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    fn header() -> Vec<String> {
+        row(&["Nama", "Umur", "Kode Sekolah"])
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let expr = FilterExpr::parse("Nama ~ budi && Umur in 10..20 || 3 = 7", Some(&header())).unwrap();
+        assert!(expr.matches(&row(&["Budi", "15", "1"])));
+        assert!(!expr.matches(&row(&["Budi", "25", "1"])));
+        assert!(expr.matches(&row(&["Ani", "25", "7"])));
+    }
+
+    #[test]
+    fn quoted_value_keeps_operators() {
+        let expr = FilterExpr::parse(r#"Nama ~ "a||b" && Umur not empty"#, Some(&header())).unwrap();
+        assert!(expr.matches(&row(&["xa||by", "1", ""])));
+        assert!(!expr.matches(&row(&["a", "1", ""])));
+
+        let expr = FilterExpr::parse(r#"1 =~ "^(x|y)&&z$""#, None).unwrap();
+        assert!(expr.matches(&row(&["y&&z"])));
+    }
+
+    #[test]
+    fn quoted_column_and_escaped_quote() {
+        let expr = FilterExpr::parse(r#""Kode Sekolah" = "12 \"A\"""#, Some(&header())).unwrap();
+        assert!(expr.matches(&row(&["", "", r#"12 "A""#])));
+    }
+
+    #[test]
+    fn regex_escape_is_kept() {
+        let expr = FilterExpr::parse(r#"Umur =~ "^\d+$""#, Some(&header())).unwrap();
+        assert!(expr.matches(&row(&["", "42", ""])));
+        assert!(!expr.matches(&row(&["", "4a", ""])));
+    }
+
+    #[test]
+    fn invalid_expressions_are_rejected() {
+        assert!(FilterExpr::parse(r#"Nama ~ "abc"#, Some(&header())).is_err());
+        assert!(FilterExpr::parse("Alamat = x", Some(&header())).is_err());
+        assert!(FilterExpr::parse("Nama", Some(&header())).is_err());
+        assert!(FilterExpr::parse("Umur in 1-2", Some(&header())).is_err());
+    }
+}
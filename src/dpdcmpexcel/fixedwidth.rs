@@ -0,0 +1,209 @@
+use std::{
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use encoding_rs::Encoding;
+
+use super::{
+    encoding::open_decoded,
+    errors::{DpdError, DpdResult, LoadWarning},
+};
+
+/// header file layout, satu baris per kolom
+const LAYOUT_HEADER: [&str; 4] = ["nama", "mulai", "lebar", "trim"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedColumn {
+    pub name: String,
+    /// posisi karakter pertama, mulai dari 1
+    pub start: usize,
+    pub width: usize,
+    pub trim: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixedLayout {
+    pub columns: Vec<FixedColumn>,
+}
+
+impl FixedLayout {
+    pub fn validate(&self) -> DpdResult<()> {
+        if self.columns.is_empty() {
+            return Err(DpdError::Validation(
+                "Layout fixed width belum memiliki kolom".to_owned(),
+            ));
+        }
+        for col in self.columns.iter() {
+            if col.start == 0 || col.width == 0 {
+                return Err(DpdError::Validation(format!(
+                    "Kolom layout `{}`: posisi mulai dan lebar harus lebih dari 0",
+                    col.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// posisi karakter terakhir yang dibaca oleh layout
+    pub fn line_width(&self) -> usize {
+        self.columns
+            .iter()
+            .map(|c| c.start + c.width - 1)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// potong satu baris berdasarkan posisi karakter (bukan byte)
+    pub fn split_line(&self, line: &str) -> Vec<String> {
+        let chars = line.chars().collect::<Vec<_>>();
+        self.columns
+            .iter()
+            .map(|col| {
+                let start = (col.start - 1).min(chars.len());
+                let end = (start + col.width).min(chars.len());
+                let value = chars[start..end].iter().collect::<String>();
+                if col.trim {
+                    value.trim().to_owned()
+                } else {
+                    value
+                }
+            })
+            .collect()
+    }
+
+    /// layout disimpan sebagai csv `nama,mulai,lebar,trim` supaya bisa dipakai ulang / diedit manual
+    pub fn load<P: AsRef<Path>>(path: P) -> DpdResult<Self> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .trim(csv::Trim::All)
+            .from_path(path.as_ref())?;
+        let mut columns = vec![];
+        for (idx, record) in reader.records().enumerate() {
+            let record = record?;
+            let field = |i: usize| record.get(i).unwrap_or_default();
+            let number = |i: usize| {
+                field(i).parse::<usize>().map_err(|_| {
+                    DpdError::Validation(format!(
+                        "Layout `{}` baris {}: `{}` harus berupa angka, ditemukan `{}`",
+                        path.as_ref().display(),
+                        idx + 2,
+                        LAYOUT_HEADER[i],
+                        field(i)
+                    ))
+                })
+            };
+            columns.push(FixedColumn {
+                name: field(0).to_owned(),
+                start: number(1)?,
+                width: number(2)?,
+                trim: !matches!(field(3).to_lowercase().as_str(), "false" | "0" | "tidak" | "no"),
+            });
+        }
+        let out = Self { columns };
+        out.validate()?;
+        Ok(out)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> DpdResult<()> {
+        let mut writer = csv::Writer::from_path(path)?;
+        writer.write_record(LAYOUT_HEADER)?;
+        for col in self.columns.iter() {
+            writer.write_record([
+                col.name.to_owned(),
+                col.start.to_string(),
+                col.width.to_string(),
+                col.trim.to_string(),
+            ])?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// baris pertama hasil adalah nama kolom dari layout, baris kosong dilewati
+pub(crate) fn load_fixed_width<P: AsRef<Path>>(
+    path: P,
+    encoding: &'static Encoding,
+    layout: &FixedLayout,
+) -> DpdResult<(Vec<Vec<String>>, Vec<LoadWarning>)> {
+    layout.validate()?;
+    let reader = BufReader::new(open_decoded(path, encoding)?);
+    let width = layout.line_width();
+    let mut out = vec![layout.columns.iter().map(|c| c.name.to_owned()).collect()];
+    let mut warnings = vec![];
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let len = line.chars().count();
+        if len < width {
+            warnings.push(LoadWarning {
+                line: idx as u64 + 1,
+                message: format!(
+                    "panjang baris {} karakter, lebih pendek dari layout ({} karakter)",
+                    len, width
+                ),
+            });
+        }
+        out.push(layout.split_line(&line));
+    }
+    if out.len() == 1 {
+        warnings.push(LoadWarning {
+            line: 0,
+            message: "file kosong, tidak ada baris yang dimuat".to_owned(),
+        });
+    }
+    Ok((out, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(trim: bool) -> FixedLayout {
+        let col = |name: &str, start, width| FixedColumn {
+            name: name.to_owned(),
+            start,
+            width,
+            trim,
+        };
+        FixedLayout {
+            columns: vec![col("nama", 1, 6), col("kota", 7, 7), col("umur", 14, 3)],
+        }
+    }
+
+    #[test]
+    fn split_by_chars_not_bytes() {
+        assert_eq!(
+            layout(true).split_line("José  Bogotá 31"),
+            vec!["José", "Bogotá", "31"]
+        );
+    }
+
+    #[test]
+    fn short_lines_give_empty_cells() {
+        assert_eq!(layout(true).split_line("Budi  Solo"), vec!["Budi", "Solo", ""]);
+        assert_eq!(layout(true).split_line(""), vec!["", "", ""]);
+    }
+
+    #[test]
+    fn trim_keeps_padding_when_off() {
+        assert_eq!(
+            layout(false).split_line("Budi  Solo    7"),
+            vec!["Budi  ", "Solo   ", " 7"]
+        );
+    }
+
+    #[test]
+    fn layout_save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("layout-{}.csv", std::process::id()));
+        let mut saved = layout(true);
+        saved.columns[1].trim = false;
+        saved.save(&path).unwrap();
+        let loaded = FixedLayout::load(&path);
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded.unwrap(), saved);
+    }
+}
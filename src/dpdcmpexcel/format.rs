@@ -0,0 +1,272 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+use calamine::{open_workbook, Ods, Sheets, Xls, Xlsb, Xlsx};
+
+use super::{
+    encoding,
+    errors::{DpdError, DpdResult},
+};
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const ZIP_EMPTY_MAGIC: &[u8] = b"PK\x05\x06";
+const OLE2_MAGIC: &[u8] = &[0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+const ODS_MIMETYPE: &[u8] = b"application/vnd.oasis.opendocument.spreadsheet";
+/// jumlah byte awal yang diperiksa untuk menentukan file text atau binary
+const TEXT_SAMPLE: u64 = 8 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExcelFormat {
+    Xlsx,
+    Xlsb,
+    Xls,
+    Ods,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Excel(ExcelFormat),
+    Sqlite,
+    Json,
+    JsonLines,
+    Text,
+}
+
+/// tentukan format file dari isi file (magic bytes), bukan dari extension
+pub fn sniff<P: AsRef<Path>>(path: P) -> DpdResult<FileFormat> {
+    let mut head = vec![];
+    File::open(path.as_ref())?
+        .take(TEXT_SAMPLE)
+        .read_to_end(&mut head)?;
+
+    if head.starts_with(ZIP_MAGIC) {
+        return sniff_zip(path.as_ref()).map(FileFormat::Excel);
+    }
+    if head.starts_with(OLE2_MAGIC) {
+        return Ok(FileFormat::Excel(ExcelFormat::Xls));
+    }
+    if head.starts_with(SQLITE_MAGIC) {
+        return Ok(FileFormat::Sqlite);
+    }
+    if is_text(&head) {
+        return Ok(sniff_json(&head).unwrap_or(FileFormat::Text));
+    }
+    Err(DpdError::Validation(format!(
+        "format file `{}` tidak didukung, ditemukan {}",
+        path.as_ref().display(),
+        describe_binary(&head)
+    )))
+}
+
+/// xlsx, xlsb dan ods sama-sama arsip zip, dibedakan dari isi arsipnya
+fn sniff_zip(path: &Path) -> DpdResult<ExcelFormat> {
+    let mut zip = zip::ZipArchive::new(BufReader::new(File::open(path)?)).map_err(|e| {
+        DpdError::Validation(format!(
+            "file `{}` terlihat seperti arsip zip tapi rusak | `{}`",
+            path.display(),
+            e
+        ))
+    })?;
+    let has = |name: &str| zip.file_names().any(|n| n.eq_ignore_ascii_case(name));
+    if has("xl/workbook.xml") {
+        return Ok(ExcelFormat::Xlsx);
+    }
+    if has("xl/workbook.bin") {
+        return Ok(ExcelFormat::Xlsb);
+    }
+    let found = if has("word/document.xml") {
+        "dokumen Word (docx)"
+    } else if has("ppt/presentation.xml") {
+        "presentasi PowerPoint (pptx)"
+    } else {
+        let mut mimetype = vec![];
+        if let Ok(f) = zip.by_name("mimetype") {
+            f.take(128).read_to_end(&mut mimetype)?;
+        }
+        if mimetype.starts_with(ODS_MIMETYPE) {
+            return Ok(ExcelFormat::Ods);
+        }
+        "arsip zip yang bukan workbook excel / ods"
+    };
+    Err(DpdError::Validation(format!(
+        "format file `{}` tidak didukung, ditemukan {}",
+        path.display(),
+        found
+    )))
+}
+
+/// text jika ada BOM / terdeteksi UTF-16, atau tidak ada byte NUL dan hampir tanpa karakter kontrol
+fn is_text(sample: &[u8]) -> bool {
+    let encoding = encoding::detect(sample);
+    if encoding == encoding_rs::UTF_16LE || encoding == encoding_rs::UTF_16BE {
+        return true;
+    }
+    if sample.contains(&0) {
+        return false;
+    }
+    let control = sample
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0C | 0x1B))
+        .count();
+    control * 100 <= sample.len()
+}
+
+/// csv tidak pernah diawali `[` atau `{`, json lines berisi satu object utuh per baris
+fn sniff_json(head: &[u8]) -> Option<FileFormat> {
+    let (text, _) = encoding::detect(head).decode_with_bom_removal(head);
+    let text = text.trim_start();
+    if text.starts_with('[') {
+        return Some(FileFormat::Json);
+    }
+    if !text.starts_with('{') {
+        return None;
+    }
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    let first = lines.next().unwrap_or_default();
+    match lines.next() {
+        Some(second) if first.ends_with('}') && second.starts_with('{') => {
+            Some(FileFormat::JsonLines)
+        }
+        _ => Some(FileFormat::Json),
+    }
+}
+
+fn describe_binary(head: &[u8]) -> &'static str {
+    match head {
+        h if h.starts_with(ZIP_EMPTY_MAGIC) => "arsip zip kosong",
+        h if h.starts_with(b"%PDF") => "dokumen PDF",
+        h if h.starts_with(b"\x89PNG") => "gambar PNG",
+        h if h.starts_with(b"\xFF\xD8\xFF") => "gambar JPEG",
+        h if h.starts_with(b"\x1F\x8B") => "arsip gzip",
+        h if h.starts_with(b"Rar!") => "arsip RAR",
+        h if h.starts_with(b"7z\xBC\xAF") => "arsip 7z",
+        _ => "file binary yang tidak dikenal",
+    }
+}
+
+/// buka workbook sesuai format hasil sniff, tidak bergantung pada extension file
+pub(crate) fn open_workbook_as<P: AsRef<Path>>(
+    path: P,
+    format: ExcelFormat,
+) -> Result<Sheets, calamine::Error> {
+    Ok(match format {
+        ExcelFormat::Xlsx => {
+            Sheets::Xlsx(open_workbook::<Xlsx<_>, _>(path).map_err(calamine::Error::Xlsx)?)
+        }
+        ExcelFormat::Xlsb => {
+            Sheets::Xlsb(open_workbook::<Xlsb<_>, _>(path).map_err(calamine::Error::Xlsb)?)
+        }
+        ExcelFormat::Xls => {
+            Sheets::Xls(open_workbook::<Xls<_>, _>(path).map_err(calamine::Error::Xls)?)
+        }
+        ExcelFormat::Ods => {
+            Sheets::Ods(open_workbook::<Ods<_>, _>(path).map_err(calamine::Error::Ods)?)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn sniff_bytes(name: &str, data: &[u8]) -> DpdResult<FileFormat> {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        std::fs::write(&path, data).unwrap();
+        let out = sniff(&path);
+        std::fs::remove_file(&path).ok();
+        out
+    }
+
+    fn zip_with(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(vec![]));
+        for (name, data) in entries {
+            zip.start_file(*name, zip::write::FileOptions::default())
+                .unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn zip_workbooks_by_archive_entries() {
+        let xlsx = zip_with(&[("[Content_Types].xml", b""), ("xl/workbook.xml", b"")]);
+        let xlsb = zip_with(&[("xl/workbook.bin", b"")]);
+        let ods = zip_with(&[("mimetype", ODS_MIMETYPE), ("content.xml", b"")]);
+        assert_eq!(
+            sniff_bytes("a.xlsx", &xlsx).unwrap(),
+            FileFormat::Excel(ExcelFormat::Xlsx)
+        );
+        assert_eq!(
+            sniff_bytes("a.xlsb", &xlsb).unwrap(),
+            FileFormat::Excel(ExcelFormat::Xlsb)
+        );
+        assert_eq!(
+            sniff_bytes("a.ods", &ods).unwrap(),
+            FileFormat::Excel(ExcelFormat::Ods)
+        );
+        let docx = zip_with(&[("word/document.xml", b"")]);
+        assert!(matches!(
+            sniff_bytes("a.docx", &docx),
+            Err(DpdError::Validation(msg)) if msg.contains("docx")
+        ));
+    }
+
+    #[test]
+    fn renamed_file_is_detected_by_content() {
+        let xlsx = zip_with(&[("xl/workbook.xml", b"")]);
+        assert_eq!(
+            sniff_bytes("laporan.csv", &xlsx).unwrap(),
+            FileFormat::Excel(ExcelFormat::Xlsx)
+        );
+        assert_eq!(
+            sniff_bytes("data.xlsx", b"nama,umur\nbudi,20\n").unwrap(),
+            FileFormat::Text
+        );
+    }
+
+    #[test]
+    fn ole2_and_sqlite_magic() {
+        let mut xls = OLE2_MAGIC.to_vec();
+        xls.resize(512, 0);
+        assert_eq!(
+            sniff_bytes("a.xls", &xls).unwrap(),
+            FileFormat::Excel(ExcelFormat::Xls)
+        );
+        let mut db = SQLITE_MAGIC.to_vec();
+        db.resize(100, 0);
+        assert_eq!(sniff_bytes("a.db", &db).unwrap(), FileFormat::Sqlite);
+    }
+
+    #[test]
+    fn json_json_lines_and_text() {
+        assert_eq!(
+            sniff_bytes("a.txt", b"  [{\"a\": 1}, {\"a\": 2}]").unwrap(),
+            FileFormat::Json
+        );
+        assert_eq!(
+            sniff_bytes("a.txt", b"{\n  \"a\": 1\n}\n").unwrap(),
+            FileFormat::Json
+        );
+        assert_eq!(
+            sniff_bytes("a.txt", b"{\"a\": 1}\n{\"a\": 2}\n").unwrap(),
+            FileFormat::JsonLines
+        );
+        assert_eq!(
+            sniff_bytes("a.json", b"\xEF\xBB\xBFa;b\n1;2\n").unwrap(),
+            FileFormat::Text
+        );
+    }
+
+    #[test]
+    fn unknown_binary_is_rejected() {
+        assert!(matches!(
+            sniff_bytes("a.csv", b"%PDF-1.4\0\0\0"),
+            Err(DpdError::Validation(msg)) if msg.contains("PDF")
+        ));
+    }
+}
@@ -0,0 +1,48 @@
+use similar::{ChangeTag, TextDiff};
+
+/// Potongan text hasil diff inline di dalam satu cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fragment {
+    pub tag: ChangeTag,
+    pub text: String,
+}
+
+/// diff per kata jika cell berisi kalimat (alamat, catatan), selain itu per karakter
+pub fn diff_cell(old: &str, new: &str) -> Vec<Fragment> {
+    let diff = if old.contains(char::is_whitespace) || new.contains(char::is_whitespace) {
+        TextDiff::from_words(old, new)
+    } else {
+        TextDiff::from_chars(old, new)
+    };
+
+    let mut out: Vec<Fragment> = Vec::new();
+    for change in diff.iter_all_changes() {
+        let tag = change.tag();
+        match out.last_mut() {
+            Some(last) if last.tag == tag => last.text.push_str(change.value()),
+            _ => out.push(Fragment {
+                tag,
+                text: change.value().to_owned(),
+            }),
+        }
+    }
+    out
+}
+
+/// diff inline untuk setiap cell pada baris yang berubah, `old` dan `new` disejajarkan per kolom
+pub fn diff_row(old: &[String], new: &[String]) -> Vec<Vec<Fragment>> {
+    (0..old.len().max(new.len()))
+        .map(|idx| match (old.get(idx), new.get(idx)) {
+            (Some(o), Some(n)) => diff_cell(o, n),
+            (Some(o), None) => vec![Fragment {
+                tag: ChangeTag::Delete,
+                text: o.to_owned(),
+            }],
+            (None, Some(n)) => vec![Fragment {
+                tag: ChangeTag::Insert,
+                text: n.to_owned(),
+            }],
+            (None, None) => vec![],
+        })
+        .collect()
+}
@@ -0,0 +1,212 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read},
+    path::Path,
+};
+
+use serde_json::{Map, Value};
+
+use super::{
+    encoding::{detect_file, open_decoded},
+    errors::{DpdError, DpdResult, LoadWarning},
+};
+
+/// array berisi object (atau object yang memiliki field berupa array object) menjadi tabel,
+/// field bertingkat menjadi header dengan path bertitik, contoh `alamat.kota`
+pub(crate) fn load_json<P: AsRef<Path>>(path: P) -> DpdResult<Vec<Vec<String>>> {
+    let mut text = String::new();
+    open_decoded(path.as_ref(), detect_file(path.as_ref())?)?.read_to_string(&mut text)?;
+    let value: Value = serde_json::from_str(&text).map_err(|e| {
+        DpdError::Validation(format!(
+            "File json `{}` tidak valid | `{}`",
+            path.as_ref().display(),
+            e
+        ))
+    })?;
+    let records = match value {
+        Value::Array(items) => items,
+        Value::Object(mut map) => {
+            let key = map
+                .iter()
+                .find(|(_, v)| is_record_array(v))
+                .map(|(k, _)| k.to_owned());
+            match key.and_then(|k| map.remove(&k)) {
+                Some(Value::Array(items)) => items,
+                _ => vec![Value::Object(map)],
+            }
+        }
+        scalar => vec![scalar],
+    };
+    Ok(to_table(records.iter()))
+}
+
+fn is_record_array(value: &Value) -> bool {
+    matches!(value, Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object))
+}
+
+/// satu object json per baris, baris yang tidak valid dilewati dan dicatat sebagai warning
+pub(crate) fn load_json_lines<P: AsRef<Path>>(
+    path: P,
+) -> DpdResult<(Vec<Vec<String>>, Vec<LoadWarning>)> {
+    let reader = BufReader::new(open_decoded(path.as_ref(), detect_file(path.as_ref())?)?);
+    let mut records = vec![];
+    let mut warnings = vec![];
+    for (idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Value>(&line) {
+            Ok(value) => records.push(value),
+            Err(e) => warnings.push(LoadWarning {
+                line: idx as u64 + 1,
+                message: format!("json tidak valid, baris dilewati | {}", e),
+            }),
+        }
+    }
+    if records.is_empty() && warnings.is_empty() {
+        warnings.push(LoadWarning {
+            line: 0,
+            message: "file kosong, tidak ada baris yang dimuat".to_owned(),
+        });
+    }
+    Ok((to_table(records.iter()), warnings))
+}
+
+/// baris pertama adalah header, urutan kolom mengikuti kemunculan pertama field
+fn to_table<'a, I: Iterator<Item = &'a Value>>(records: I) -> Vec<Vec<String>> {
+    let mut header: Vec<String> = vec![];
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut rows = vec![];
+    for record in records {
+        let mut fields = vec![];
+        match record {
+            Value::Object(map) => flatten(map, "", &mut fields),
+            other => fields.push(("value".to_owned(), scalar(other))),
+        }
+        let mut row = vec![String::new(); header.len()];
+        for (key, value) in fields {
+            let col = *index.entry(key.clone()).or_insert_with(|| {
+                header.push(key);
+                header.len() - 1
+            });
+            if col >= row.len() {
+                row.resize(col + 1, String::new());
+            }
+            row[col] = value;
+        }
+        rows.push(row);
+    }
+    if header.is_empty() {
+        return vec![];
+    }
+    for row in rows.iter_mut() {
+        row.resize(header.len(), String::new());
+    }
+    rows.insert(0, header);
+    rows
+}
+
+fn flatten(map: &Map<String, Value>, prefix: &str, out: &mut Vec<(String, String)>) {
+    for (key, value) in map {
+        let path = if prefix.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Object(inner) if !inner.is_empty() => flatten(inner, &path, out),
+            other => out.push((path, scalar(other))),
+        }
+    }
+}
+
+/// array disimpan sebagai text json dalam satu cell
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.to_owned(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(name: &str, text: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    #[test]
+    fn nested_objects_become_dotted_headers() {
+        let path = write(
+            "nested.json",
+            r#"[{"nama": "budi", "alamat": {"kota": "solo", "pos": {"kode": 57100}}}]"#,
+        );
+        let rows = load_json(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["nama", "alamat.kota", "alamat.pos.kode"],
+                vec!["budi", "solo", "57100"],
+            ]
+        );
+    }
+
+    #[test]
+    fn arrays_are_kept_as_json_text() {
+        let path = write(
+            "array.json",
+            r#"{"versi": 2, "data": [{"id": 1, "tag": ["a", "b"], "kosong": {}, "x": null}]}"#,
+        );
+        let rows = load_json(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["id", "tag", "kosong", "x"],
+                vec!["1", r#"["a","b"]"#, "{}", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn mixed_keys_across_records() {
+        let path = write(
+            "mixed.json",
+            r#"[{"a": 1, "b": 2}, {"c": 3}, {"b": true, "a": "x"}]"#,
+        );
+        let rows = load_json(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["a", "b", "c"],
+                vec!["1", "2", ""],
+                vec!["", "", "3"],
+                vec!["x", "true", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn json_lines_skip_invalid_lines() {
+        let path = write(
+            "lines.jsonl",
+            "{\"id\": 1, \"u\": {\"n\": \"a\"}}\n\n{rusak\n{\"id\": 2}\n",
+        );
+        let (rows, warnings) = load_json_lines(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            rows,
+            vec![vec!["id", "u.n"], vec!["1", "a"], vec!["2", ""]]
+        );
+        assert_eq!(warnings.iter().map(|w| w.line).collect::<Vec<_>>(), vec![3]);
+    }
+}
@@ -1,6 +1,22 @@
+pub mod batch;
+pub mod cell;
 pub mod compares;
 pub mod deserializer;
+pub mod dialect;
+pub mod encoding;
 pub mod errors;
+pub mod filter;
+pub mod fixedwidth;
+pub mod format;
+pub mod inline;
+pub mod json;
+pub mod paste;
+pub mod resultfilter;
+pub mod schema;
+pub mod search;
+pub mod sqlite;
+pub mod stream;
+pub mod transform;
 
 use std::{fmt, fmt::Display};
 
@@ -13,12 +29,12 @@ pub use self::{
 pub(crate) struct Line(String, Option<usize>);
 impl fmt::Display for Line {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let sz = self.0.len();
-        let st = if sz > 30 { &self.0[0..30] } else { &self.0 };
+        // dihitung per karakter, text bisa berisi huruf non-ascii
+        let width = self.0.chars().count().min(30);
         match self.1 {
             None => write!(f, "    "),
             Some(idx) => {
-                for _ in 0..31 - st.len() {
+                for _ in 0..31 - width {
                     write!(f, " ")?
                 }
                 write!(f, "{:>4}", idx + 1)
@@ -32,7 +48,12 @@ pub struct LimitedVec<T>(pub Vec<T>);
 impl fmt::Display for LimitedVec<String> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for i in self.0.iter() {
-            write!(f, "|{:.<10}", if i.len() <= 10 { &i } else { &i[..8] })?;
+            // dipotong per karakter, bukan per byte
+            if i.chars().count() <= 10 {
+                write!(f, "|{:.<10}", i)?;
+            } else {
+                write!(f, "|{:.<10}", i.chars().take(8).collect::<String>())?;
+            }
         }
         Ok(())
     }
@@ -51,31 +72,44 @@ impl Display for CmpRslt {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub col: usize,
+    pub descending: bool,
+}
+
 pub trait SortVec {
     type ReturnType;
-    fn filter_col(&mut self, size: usize) -> Self::ReturnType;
-    fn sort_by_col(&mut self, idx_col: usize) -> Self::ReturnType;
+    fn sort_by_keys(&mut self, keys: &[SortKey]) -> Self::ReturnType;
 }
-impl SortVec for Vec<Vec<String>> {
+impl SortVec for [Vec<String>] {
     type ReturnType = DpdResult<()>;
-    #[inline(always)]
-    fn filter_col(&mut self, size_row: usize) -> Self::ReturnType {
-        self.retain(|f| f.len() == size_row);
-        Ok(())
-    }
-
-    #[inline(always)]
-    fn sort_by_col(&mut self, idx_col: usize) -> Self::ReturnType {
-        if idx_col >= self.len() {
-            return Err(DpdError::Processing(
-                "Error on Shorting Vector of data excel!".to_owned(),
-            ));
+    /// sort stabil berdasarkan beberapa kolom, angka dan tanggal dibandingkan sesuai nilainya
+    fn sort_by_keys(&mut self, keys: &[SortKey]) -> Self::ReturnType {
+        let width = self.iter().map(|r| r.len()).max().unwrap_or(0);
+        if let Some(key) = keys.iter().find(|k| k.col >= width) {
+            return Err(DpdError::Processing(format!(
+                "Error on Shorting Vector of data excel! kolom {} diluar lebar tabel {}",
+                key.col + 1,
+                width
+            )));
         }
-        self.sort_by(|a, b| a[idx_col].to_lowercase().cmp(&b[idx_col].to_lowercase()));
+        self.sort_by(|a, b| {
+            keys.iter()
+                .map(|k| {
+                    let a = a.get(k.col).map(String::as_str).unwrap_or_default();
+                    let b = b.get(k.col).map(String::as_str).unwrap_or_default();
+                    // cell kosong tetap di akhir walaupun urutan dibalik
+                    match (a.trim().is_empty(), b.trim().is_empty()) {
+                        (false, false) if k.descending => cell::compare_cells(a, b).reverse(),
+                        _ => cell::compare_cells(a, b),
+                    }
+                })
+                .find(|o| o.is_ne())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
         Ok(())
     }
-
-    // add code here
 }
 pub trait Identic {
     type Type;
@@ -101,6 +135,69 @@ pub struct CmpRslt {
     pub file: String,
     pub sheet: String,
     pub data: Vec<String>,
+    pub pair: Option<Vec<String>>,
+    pub moved: Option<(usize, usize)>,
+}
+
+impl CmpRslt {
+    /// nomor baris yang ditampilkan ke pengguna, dimulai dari 1
+    #[inline]
+    pub fn number(&self) -> usize {
+        self.index + 1
+    }
+
+    /// baris yang berubah isinya (bukan sekedar dihapus / ditambah)
+    #[inline]
+    pub fn is_modified(&self) -> bool {
+        self.pair.is_some()
+    }
+
+    /// baris identik yang hanya berpindah posisi, `(index sumber, index target)`
+    #[inline]
+    pub fn is_moved(&self) -> bool {
+        self.moved.is_some()
+    }
+
+    pub fn kind(&self) -> resultfilter::ChangeKind {
+        use resultfilter::ChangeKind;
+        match self.tag {
+            _ if self.is_moved() => ChangeKind::Moved,
+            _ if self.is_modified() => ChangeKind::Modified,
+            similar::ChangeTag::Insert => ChangeKind::Inserted,
+            _ => ChangeKind::Deleted,
+        }
+    }
+
+    /// posisi kolom yang isinya berbeda dengan pasangannya, kosong jika bukan MODIFIED
+    pub fn changed_columns(&self) -> Vec<usize> {
+        let pair = match &self.pair {
+            Some(pair) => pair,
+            None => return vec![],
+        };
+        (0..self.data.len().max(pair.len()))
+            .filter(|&idx| self.data.get(idx) != pair.get(idx))
+            .collect()
+    }
+
+    pub fn tag_text(&self) -> String {
+        match (self.tag, self.moved) {
+            (_, Some((from, to))) => format!("(>)MOVED {} -> {}", from + 1, to + 1),
+            _ if self.is_modified() => "(~)MODIFIED".to_owned(),
+            (similar::ChangeTag::Delete, _) => "(-)DELETED".to_owned(),
+            (similar::ChangeTag::Insert, _) => "(+)INSERTED".to_owned(),
+            (similar::ChangeTag::Equal, _) => "(=)EQUAL".to_owned(),
+        }
+    }
+
+    /// diff inline per cell, selalu dari sisi sumber ke sisi target
+    pub fn inline_diff(&self) -> Option<Vec<Vec<inline::Fragment>>> {
+        let pair = self.pair.as_ref()?;
+        Some(if self.issrc {
+            inline::diff_row(&self.data, pair)
+        } else {
+            inline::diff_row(pair, &self.data)
+        })
+    }
 }
 
 impl Default for CmpRslt {
@@ -112,6 +209,60 @@ impl Default for CmpRslt {
             file: Default::default(),
             sheet: Default::default(),
             data: Default::default(),
+            pair: Default::default(),
+            moved: Default::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limited_vec_truncates_multibyte_text_by_chars() {
+        let row = LimitedVec(vec![
+            "aéééééééééé".to_owned(),
+            "José".to_owned(),
+            "Bogotá".to_owned(),
+        ]);
+        assert_eq!(row.to_string(), "|aééééééé..|José......|Bogotá....");
+    }
+
+    #[test]
+    fn display_result_with_multibyte_data() {
+        let item = CmpRslt {
+            index: 2,
+            data: vec!["ñññññññññññññ".to_owned()],
+            ..Default::default()
+        };
+        assert!(item.to_string().ends_with("|ññññññññ.."));
+    }
+
+    #[test]
+    fn moved_rows_are_numbered_like_the_row_index() {
+        let item = CmpRslt {
+            moved: Some((0, 4)),
+            ..Default::default()
+        };
+        assert_eq!(item.tag_text(), "(>)MOVED 1 -> 5");
+    }
+
+    #[test]
+    fn descending_sort_keeps_empty_cells_last() {
+        let mut rows = vec![
+            vec!["2".to_owned()],
+            vec!["".to_owned()],
+            vec!["10".to_owned()],
+            vec!["".to_owned()],
+            vec!["1".to_owned()],
+        ];
+        rows.sort_by_keys(&[SortKey {
+            col: 0,
+            descending: true,
+        }])
+        .unwrap();
+        let sorted = rows.iter().map(|r| r[0].as_str()).collect::<Vec<_>>();
+        assert_eq!(sorted, vec!["10", "2", "1", "", ""]);
+    }
+}
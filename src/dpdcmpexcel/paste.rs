@@ -0,0 +1,204 @@
+use tl::{Node, NodeHandle, Parser, ParserOptions};
+
+use super::{deserializer::deserialize_data_csv, dialect::CsvDialect, errors::LoadWarning};
+
+/// hasil parsing text dari clipboard
+pub struct Pasted {
+    pub data: Vec<Vec<String>>,
+    pub has_header: bool,
+    pub warnings: Vec<LoadWarning>,
+}
+
+/// text dari clipboard: markup tabel html (contoh hasil copy source halaman web) atau text
+/// dengan pemisah tab / koma / titik koma (copy dari excel, spreadsheet atau text editor)
+pub fn parse_clipboard(text: &str) -> Pasted {
+    if text.to_ascii_lowercase().contains("<table") {
+        if let Some(pasted) = parse_html_table(text) {
+            return pasted;
+        }
+    }
+    let dialect = CsvDialect::sniff(text.as_bytes());
+    let mut reader = dialect.reader_builder().from_reader(text.as_bytes());
+    let (data, warnings) = deserialize_data_csv(&mut reader);
+    Pasted {
+        data,
+        has_header: dialect.has_header,
+        warnings,
+    }
+}
+
+/// ambil tabel html pertama. baris pertama dianggap header jika berada di `<thead>`
+/// atau semua cellnya `<th>`, cell dengan `colspan` diisi cell kosong agar kolom tidak bergeser
+fn parse_html_table(html: &str) -> Option<Pasted> {
+    let dom = tl::parse(html, ParserOptions::default()).ok()?;
+    let parser = dom.parser();
+    let table = dom.nodes().iter().find(|n| is_tag(n, "table"))?;
+    let mut rows = vec![];
+    collect_rows(table, parser, false, &mut rows);
+
+    let mut has_header = false;
+    let mut data = vec![];
+    for (tr, in_head) in rows {
+        let mut header_row = true;
+        let mut cells = vec![];
+        for node in children(tr, parser) {
+            let is_th = is_tag(node, "th");
+            if !is_th && !is_tag(node, "td") {
+                continue;
+            }
+            header_row &= is_th;
+            let mut text = String::new();
+            cell_text(node, parser, &mut text);
+            cells.push(text.split_whitespace().collect::<Vec<_>>().join(" "));
+            let span = node
+                .as_tag()
+                .and_then(|t| t.attributes().get("colspan").flatten())
+                .and_then(|v| v.as_utf8_str().trim().parse::<usize>().ok())
+                .unwrap_or(1);
+            cells.resize(cells.len() + span.saturating_sub(1), String::new());
+        }
+        if cells.is_empty() {
+            continue;
+        }
+        if data.is_empty() {
+            has_header = in_head || header_row;
+        }
+        data.push(cells);
+    }
+    if data.is_empty() {
+        return None;
+    }
+    Some(Pasted {
+        data,
+        has_header,
+        warnings: vec![],
+    })
+}
+
+fn is_tag(node: &Node, name: &str) -> bool {
+    node.as_tag()
+        .is_some_and(|t| t.name().as_utf8_str().eq_ignore_ascii_case(name))
+}
+
+fn children<'p, 'a>(node: &Node<'a>, parser: &'p Parser<'a>) -> impl Iterator<Item = &'p Node<'a>> {
+    node.as_tag()
+        .map(|t| t.children().top().to_vec())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(move |h: NodeHandle| h.get(parser))
+}
+
+/// baris `<tr>` milik tabel ini saja, tabel lain yang bersarang di dalam cell tidak ikut
+fn collect_rows<'p, 'a>(
+    node: &Node<'a>,
+    parser: &'p Parser<'a>,
+    in_head: bool,
+    out: &mut Vec<(&'p Node<'a>, bool)>,
+) {
+    for child in children(node, parser) {
+        if is_tag(child, "tr") {
+            out.push((child, in_head));
+        } else if ["thead", "tbody", "tfoot"].iter().any(|n| is_tag(child, n)) {
+            collect_rows(child, parser, is_tag(child, "thead"), out);
+        }
+    }
+}
+
+/// text cell tanpa markup, `<br>` menjadi spasi dan entity html didecode
+fn cell_text(node: &Node, parser: &Parser, out: &mut String) {
+    match node {
+        Node::Raw(text) => out.push_str(&decode_entities(&text.as_utf8_str())),
+        Node::Tag(tag) if tag.name().as_utf8_str().eq_ignore_ascii_case("br") => out.push(' '),
+        Node::Tag(_) => {
+            for child in children(node, parser) {
+                cell_text(child, parser, out);
+            }
+        }
+        Node::Comment(_) => {}
+    }
+}
+
+fn decode_entities(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_owned();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let c = match &rest[1..end] {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                e if e.starts_with("#x") || e.starts_with("#X") => {
+                    u32::from_str_radix(&e[2..], 16).ok().and_then(char::from_u32)
+                }
+                e if e.starts_with('#') => e[1..].parse().ok().and_then(char::from_u32),
+                _ => None,
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_copied_from_browser_is_tab_separated() {
+        let pasted = parse_clipboard("Nama\tUmur\r\nBudi, S.Pd\t20\r\nSiti\t31\r\n");
+        assert!(pasted.has_header);
+        assert_eq!(pasted.data[1], vec!["Budi, S.Pd", "20"]);
+        assert_eq!(pasted.data.len(), 3);
+    }
+
+    #[test]
+    fn html_table_is_parsed_with_header_and_entities() {
+        let html = concat!(
+            "<html><body><!-- judul --><TABLE class=\"data\" title=\"a > b\">",
+            "<thead><tr><td>Nama</td><td data-x='</td>'>Umur</td></tr></thead>",
+            "<tbody><tr><td><b>Budi</b>, S.Pd</td><td>20</td></tr>",
+            "<tr><td>Siti<br>Aminah &amp; Co</td><td>&#51;1</td></tr>",
+            "<tr><td colspan=\"2\">Total</td></tr>",
+            "<tr><td><table><tr><td>dalam</td></tr></table></td><td>9</td></tr>",
+            "</tbody></TABLE></body></html>"
+        );
+        let pasted = parse_clipboard(html);
+        assert!(pasted.has_header);
+        assert_eq!(
+            pasted.data,
+            vec![
+                vec!["Nama", "Umur"],
+                vec!["Budi, S.Pd", "20"],
+                vec!["Siti Aminah & Co", "31"],
+                vec!["Total", ""],
+                vec!["dalam", "9"],
+            ]
+        );
+    }
+
+    #[test]
+    fn html_table_without_th_has_no_header() {
+        let pasted = parse_clipboard("<table><tr><td>1</td><td>2</td></tr></table>");
+        assert!(!pasted.has_header);
+        assert_eq!(pasted.data, vec![vec!["1", "2"]]);
+    }
+}
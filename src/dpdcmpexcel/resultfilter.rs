@@ -0,0 +1,131 @@
+use super::{search::Search, CmpRslt};
+
+/// jenis perubahan satu baris hasil perbandingan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Inserted,
+    Deleted,
+    Modified,
+    Moved,
+}
+
+impl ChangeKind {
+    pub const ALL: [Self; 4] = [Self::Inserted, Self::Deleted, Self::Modified, Self::Moved];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Inserted => "INSERTED",
+            Self::Deleted => "DELETED",
+            Self::Modified => "MODIFIED",
+            Self::Moved => "MOVED",
+        }
+    }
+}
+
+/// satu nilai untuk setiap `ChangeKind`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerKind<T> {
+    pub inserted: T,
+    pub deleted: T,
+    pub modified: T,
+    pub moved: T,
+}
+
+impl<T> PerKind<T> {
+    pub fn get(&self, kind: ChangeKind) -> &T {
+        match kind {
+            ChangeKind::Inserted => &self.inserted,
+            ChangeKind::Deleted => &self.deleted,
+            ChangeKind::Modified => &self.modified,
+            ChangeKind::Moved => &self.moved,
+        }
+    }
+
+    pub fn get_mut(&mut self, kind: ChangeKind) -> &mut T {
+        match kind {
+            ChangeKind::Inserted => &mut self.inserted,
+            ChangeKind::Deleted => &mut self.deleted,
+            ChangeKind::Modified => &mut self.modified,
+            ChangeKind::Moved => &mut self.moved,
+        }
+    }
+}
+
+/// saringan tabel output, baris ditampilkan jika lolos semua kriteria
+#[derive(Debug, Clone)]
+pub struct ResultFilter {
+    pub kinds: PerKind<bool>,
+    /// hanya baris MODIFIED yang isi kolom ini berubah
+    pub column: Option<usize>,
+    pub text: Option<Search>,
+}
+
+impl Default for ResultFilter {
+    fn default() -> Self {
+        Self {
+            kinds: PerKind {
+                inserted: true,
+                deleted: true,
+                modified: true,
+                moved: true,
+            },
+            column: None,
+            text: None,
+        }
+    }
+}
+
+impl ResultFilter {
+    pub fn matches(&self, item: &CmpRslt) -> bool {
+        *self.kinds.get(item.kind())
+            && self
+                .column
+                .is_none_or(|col| item.changed_columns().contains(&col))
+            && self.text.as_ref().is_none_or(|s| s.row_matches(&item.data))
+    }
+
+    /// posisi baris yang lolos saringan, berurutan
+    pub fn apply(&self, items: &[CmpRslt]) -> Vec<usize> {
+        items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| self.matches(item))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+}
+
+/// kelompokkan posisi baris berdasarkan sheet, urutan sheet mengikuti kemunculan pertama
+pub fn group_by_sheet(items: &[CmpRslt], positions: &[usize]) -> Vec<(String, Vec<usize>)> {
+    let mut groups: Vec<(String, Vec<usize>)> = vec![];
+    for &pos in positions {
+        let sheet = &items[pos].sheet;
+        match groups.iter_mut().find(|(name, _)| name == sheet) {
+            Some((_, rows)) => rows.push(pos),
+            None => groups.push((sheet.to_owned(), vec![pos])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unchecked_kind_is_hidden() {
+        let items = vec![
+            CmpRslt {
+                tag: similar::ChangeTag::Delete,
+                ..Default::default()
+            },
+            CmpRslt {
+                tag: similar::ChangeTag::Insert,
+                ..Default::default()
+            },
+        ];
+        let mut filter = ResultFilter::default();
+        *filter.kinds.get_mut(ChangeKind::Deleted) = false;
+        assert_eq!(filter.apply(&items), vec![1]);
+    }
+}
@@ -0,0 +1,350 @@
+use std::{collections::HashSet, fmt};
+
+use super::{cell::CellType, CmpData};
+
+/// jumlah baris maksimal yang dibaca untuk inferensi jenis dan kemiripan isi kolom
+pub(crate) const SAMPLE_ROWS: usize = 1_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    pub index: usize,
+    pub ctype: CellType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    Added {
+        name: String,
+        index: usize,
+    },
+    Removed {
+        name: String,
+        index: usize,
+    },
+    Renamed {
+        from: String,
+        to: String,
+        index: usize,
+    },
+    Reordered {
+        name: String,
+        from: usize,
+        to: usize,
+    },
+    TypeChanged {
+        name: String,
+        from: CellType,
+        to: CellType,
+    },
+}
+
+impl SchemaChange {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SchemaChange::Added { .. } => "(+)ADDED",
+            SchemaChange::Removed { .. } => "(-)REMOVED",
+            SchemaChange::Renamed { .. } => "(~)RENAMED",
+            SchemaChange::Reordered { .. } => "(>)REORDERED",
+            SchemaChange::TypeChanged { .. } => "(!)TYPE CHANGED",
+        }
+    }
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<16} {}", self.label(), self.description())
+    }
+}
+
+impl SchemaChange {
+    pub fn description(&self) -> String {
+        match self {
+            SchemaChange::Added { name, index } => {
+                format!("kolom `{}` ditambahkan di posisi {}", name, index + 1)
+            }
+            SchemaChange::Removed { name, index } => {
+                format!("kolom `{}` di posisi {} dihapus", name, index + 1)
+            }
+            SchemaChange::Renamed { from, to, index } => {
+                format!("kolom `{}` diganti nama menjadi `{}` (posisi {})", from, to, index + 1)
+            }
+            SchemaChange::Reordered { name, from, to } => {
+                format!("kolom `{}` berpindah dari posisi {} ke {}", name, from + 1, to + 1)
+            }
+            SchemaChange::TypeChanged { name, from, to } => {
+                format!("jenis kolom `{}` berubah dari {} menjadi {}", name, from, to)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SchemaDiff(pub Vec<SchemaChange>);
+
+impl SchemaDiff {
+    pub fn columns(data: &CmpData) -> Vec<Column> {
+        let body = &data.selected_data[usize::from(data.has_header).min(data.selected_data.len())..];
+        data.column_names()
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| Column {
+                name,
+                index,
+                ctype: CellType::infer_column(
+                    body.iter()
+                        .take(SAMPLE_ROWS)
+                        .map(|r| r.get(index).map(String::as_str).unwrap_or_default()),
+                ),
+            })
+            .collect()
+    }
+
+    pub fn compare(src: &CmpData, tgt: &CmpData) -> Self {
+        let (src_cols, tgt_cols) = (Self::columns(src), Self::columns(tgt));
+        let mut pairs: Vec<(usize, usize, bool)> = vec![];
+        let mut src_used = vec![false; src_cols.len()];
+        let mut tgt_used = vec![false; tgt_cols.len()];
+        let named = src.has_header && tgt.has_header;
+
+        // 1. kolom dengan nama yang sama, hanya jika kedua input memiliki header
+        if named {
+            for s in src_cols.iter() {
+                let found = tgt_cols
+                    .iter()
+                    .find(|t| !tgt_used[t.index] && t.name.eq_ignore_ascii_case(&s.name));
+                if let Some(t) = found {
+                    src_used[s.index] = true;
+                    tgt_used[t.index] = true;
+                    pairs.push((s.index, t.index, false));
+                }
+            }
+        }
+
+        // 2. sisa kolom: posisi yang sama atau isi yang mirip dianggap diganti nama
+        for s in src_cols.iter() {
+            if src_used[s.index] {
+                continue;
+            }
+            let candidate = tgt_cols
+                .iter()
+                .filter(|t| !tgt_used[t.index])
+                .map(|t| (t.index, column_similarity(src, s.index, tgt, t.index)))
+                .filter(|&(t, score)| score >= 0.5 || (t == s.index && score > 0.0))
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            let candidate = match candidate {
+                Some((t, _)) => Some(t),
+                None if !named && s.index < tgt_cols.len() && !tgt_used[s.index] => Some(s.index),
+                None => None,
+            };
+            if let Some(t) = candidate {
+                src_used[s.index] = true;
+                tgt_used[t] = true;
+                pairs.push((s.index, t, named));
+            }
+        }
+        pairs.sort_unstable();
+
+        let mut out = vec![];
+        for s in src_cols.iter().filter(|s| !src_used[s.index]) {
+            out.push(SchemaChange::Removed {
+                name: s.name.to_owned(),
+                index: s.index,
+            });
+        }
+        for t in tgt_cols.iter().filter(|t| !tgt_used[t.index]) {
+            out.push(SchemaChange::Added {
+                name: t.name.to_owned(),
+                index: t.index,
+            });
+        }
+
+        let in_order = longest_increasing(&pairs.iter().map(|p| p.1).collect::<Vec<_>>());
+        for (i, &(s, t, renamed)) in pairs.iter().enumerate() {
+            let (s, t) = (&src_cols[s], &tgt_cols[t]);
+            if renamed && s.name != t.name {
+                out.push(SchemaChange::Renamed {
+                    from: s.name.to_owned(),
+                    to: t.name.to_owned(),
+                    index: t.index,
+                });
+            }
+            if !in_order.contains(&i) {
+                out.push(SchemaChange::Reordered {
+                    name: t.name.to_owned(),
+                    from: s.index,
+                    to: t.index,
+                });
+            }
+            if s.ctype != t.ctype && s.ctype != CellType::Empty && t.ctype != CellType::Empty {
+                out.push(SchemaChange::TypeChanged {
+                    name: t.name.to_owned(),
+                    from: s.ctype,
+                    to: t.ctype,
+                });
+            }
+        }
+        Self(out)
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// baris untuk export, kolom: `Perubahan | Keterangan`
+    pub fn to_rows(&self) -> Vec<Vec<String>> {
+        let mut rows = vec![vec!["Perubahan".to_owned(), "Keterangan".to_owned()]];
+        rows.extend(
+            self.0
+                .iter()
+                .map(|c| vec![c.label().to_owned(), c.description()]),
+        );
+        rows
+    }
+}
+
+/// kemiripan isi dua kolom (jaccard dari nilai unik), `0.0..=1.0`
+fn column_similarity(src: &CmpData, s: usize, tgt: &CmpData, t: usize) -> f64 {
+    fn values(data: &CmpData, col: usize) -> HashSet<&str> {
+        let skip = usize::from(data.has_header);
+        data.selected_data
+            .iter()
+            .skip(skip)
+            .take(SAMPLE_ROWS)
+            .filter_map(|r| r.get(col).map(String::as_str))
+            .filter(|c| !c.trim().is_empty())
+            .collect()
+    }
+    let (a, b) = (values(src, s), values(tgt, t));
+    let union = a.union(&b).count();
+    if union == 0 {
+        0.0
+    } else {
+        a.intersection(&b).count() as f64 / union as f64
+    }
+}
+
+/// index elemen yang membentuk subsequence naik terpanjang,
+/// kolom di luar subsequence ini yang dianggap berpindah posisi
+fn longest_increasing(seq: &[usize]) -> HashSet<usize> {
+    let mut len = vec![1usize; seq.len()];
+    let mut prev = vec![None; seq.len()];
+    for i in 0..seq.len() {
+        for j in 0..i {
+            if seq[j] < seq[i] && len[j] + 1 > len[i] {
+                len[i] = len[j] + 1;
+                prev[i] = Some(j);
+            }
+        }
+    }
+    let mut out = HashSet::new();
+    let mut cur = (0..seq.len()).max_by_key(|&i| len[i]);
+    while let Some(i) = cur {
+        out.insert(i);
+        cur = prev[i];
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rows: &[&str]) -> CmpData {
+        let rows = rows
+            .iter()
+            .map(|r| r.split(',').map(str::to_owned).collect())
+            .collect::<Vec<Vec<String>>>();
+        let mut data = CmpData::default().with_rows("Sheet1", rows.into());
+        data.has_header = true;
+        data
+    }
+
+    fn diff(src: &[&str], tgt: &[&str]) -> Vec<SchemaChange> {
+        SchemaDiff::compare(&table(src), &table(tgt)).0
+    }
+
+    #[test]
+    fn added_and_removed_columns() {
+        let small = ["nama,umur", "budi,20", "siti,31"];
+        let wide = ["nama,umur,kota", "budi,20,solo", "siti,31,blitar"];
+        assert_eq!(
+            diff(&small, &wide),
+            vec![SchemaChange::Added {
+                name: "kota".to_owned(),
+                index: 2
+            }]
+        );
+        assert_eq!(
+            diff(&wide, &small),
+            vec![SchemaChange::Removed {
+                name: "kota".to_owned(),
+                index: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn renamed_column_with_same_content() {
+        assert_eq!(
+            diff(
+                &["nama,umur", "budi,20", "siti,31"],
+                &["nama,usia", "budi,20", "siti,31"]
+            ),
+            vec![SchemaChange::Renamed {
+                from: "umur".to_owned(),
+                to: "usia".to_owned(),
+                index: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn reordered_column() {
+        assert_eq!(
+            diff(
+                &["nama,umur,kota", "budi,20,solo"],
+                &["umur,nama,kota", "20,budi,solo"]
+            ),
+            vec![SchemaChange::Reordered {
+                name: "umur".to_owned(),
+                from: 1,
+                to: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn changed_column_type() {
+        assert_eq!(
+            diff(
+                &["nama,umur", "budi,20", "siti,31"],
+                &["nama,umur", "budi,dua puluh", "siti,tiga puluh satu"]
+            ),
+            vec![SchemaChange::TypeChanged {
+                name: "umur".to_owned(),
+                from: CellType::Number,
+                to: CellType::Text
+            }]
+        );
+    }
+
+    #[test]
+    fn same_schema_has_no_changes() {
+        let rows = ["nama,lahir", "budi,01/02/2000"];
+        assert!(diff(&rows, &rows).is_empty());
+    }
+
+    #[test]
+    fn type_is_inferred_from_sample_rows_only() {
+        let numbers = std::iter::once("id".to_owned())
+            .chain((0..SAMPLE_ROWS).map(|i| i.to_string()))
+            .collect::<Vec<_>>();
+        let mut tail = numbers.to_owned();
+        tail.extend(["x", "y", "z"].map(str::to_owned));
+        let numbers = numbers.iter().map(String::as_str).collect::<Vec<_>>();
+        let tail = tail.iter().map(String::as_str).collect::<Vec<_>>();
+        assert!(diff(&numbers, &tail).is_empty());
+    }
+}
@@ -0,0 +1,48 @@
+use super::{
+    errors::DpdResult,
+    filter::{build_op, FilterOp},
+};
+
+/// pencarian isi cell: text biasa atau regex (keduanya tidak case sensitive),
+/// bisa dibatasi pada satu kolom
+#[derive(Debug, Clone)]
+pub struct Search {
+    op: FilterOp,
+    pub column: Option<usize>,
+}
+
+impl Search {
+    /// `None` jika text pencarian kosong
+    pub fn new(text: &str, regex: bool, column: Option<usize>) -> DpdResult<Option<Self>> {
+        if text.trim().is_empty() {
+            return Ok(None);
+        }
+        let op = build_op(if regex { "=~" } else { "~" }, text)?;
+        Ok(Some(Self { op, column }))
+    }
+
+    #[inline]
+    pub fn cell_matches(&self, col: usize, cell: &str) -> bool {
+        !matches!(self.column, Some(c) if c != col) && self.op.matches(cell)
+    }
+
+    pub fn row_matches<S: AsRef<str>>(&self, row: &[S]) -> bool {
+        match self.column {
+            Some(col) => row.get(col).is_some_and(|c| self.op.matches(c.as_ref())),
+            None => row.iter().any(|c| self.op.matches(c.as_ref())),
+        }
+    }
+
+    /// posisi baris yang memiliki cell cocok, berurutan
+    pub fn find_rows<'a, I, S>(&self, rows: I) -> Vec<usize>
+    where
+        I: IntoIterator<Item = &'a [S]>,
+        S: AsRef<str> + 'a,
+    {
+        rows.into_iter()
+            .enumerate()
+            .filter(|(_, row)| self.row_matches(row))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+}
@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use rusqlite::{types::ValueRef, Connection, OpenFlags};
+
+use super::errors::{DpdError, DpdResult};
+
+/// database dibuka read-only, query yang mengubah data akan ditolak oleh sqlite
+pub(crate) fn open<P: AsRef<Path>>(path: P) -> DpdResult<Connection> {
+    Ok(Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?)
+}
+
+/// nama tabel dan view, diperlakukan seperti nama sheet pada excel
+pub(crate) fn table_names(conn: &Connection) -> DpdResult<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT name FROM sqlite_master \
+         WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' \
+         ORDER BY type, name",
+    )?;
+    let names = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(names)
+}
+
+pub(crate) fn load_table(conn: &Connection, table: &str) -> DpdResult<Vec<Vec<String>>> {
+    load_query(
+        conn,
+        &format!("SELECT * FROM \"{}\"", table.replace('"', "\"\"")),
+    )
+}
+
+/// baris pertama hasil adalah nama kolom dari query
+pub(crate) fn load_query(conn: &Connection, sql: &str) -> DpdResult<Vec<Vec<String>>> {
+    let mut stmt = conn.prepare(sql)?;
+    if stmt.column_count() == 0 {
+        return Err(DpdError::Validation(
+            "Query tidak menghasilkan kolom, gunakan query SELECT".to_owned(),
+        ));
+    }
+    let mut out = vec![stmt
+        .column_names()
+        .into_iter()
+        .map(str::to_owned)
+        .collect::<Vec<_>>()];
+    let width = out[0].len();
+    let mut rows = stmt.query([])?;
+    while let Some(row) = rows.next()? {
+        let mut cells = Vec::with_capacity(width);
+        for idx in 0..width {
+            cells.push(match row.get_ref(idx)? {
+                ValueRef::Null => String::new(),
+                ValueRef::Integer(i) => i.to_string(),
+                ValueRef::Real(f) => f.to_string(),
+                ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned(),
+                ValueRef::Blob(b) => blob_text(b),
+            });
+        }
+        out.push(cells);
+    }
+    Ok(out)
+}
+
+/// jumlah byte awal blob yang ditampilkan sebagai hex
+const BLOB_PREFIX: usize = 8;
+
+/// blob ditampilkan sebagai ukuran, hex beberapa byte awal dan hash FNV-1a seluruh isi,
+/// sehingga blob dengan ukuran sama tapi isi berbeda tetap terdeteksi berubah
+fn blob_text(blob: &[u8]) -> String {
+    let hash = blob.iter().fold(0xcbf2_9ce4_8422_2325_u64, |h, &b| {
+        (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    });
+    let prefix = blob
+        .iter()
+        .take(BLOB_PREFIX)
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    let more = if blob.len() > BLOB_PREFIX { "…" } else { "" };
+    format!("<blob {} byte {}{} #{:016x}>", blob.len(), prefix, more, hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blobs_of_same_size_render_differently() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE t (id INTEGER, data BLOB);
+             INSERT INTO t VALUES (1, x'89504e470d0a1a0a0001'), (2, x'89504e470d0a1a0a0002'),
+                                  (3, x'cafe'), (4, NULL);",
+        )
+        .unwrap();
+        let rows = load_table(&conn, "t").unwrap();
+        assert_eq!(rows[0], vec!["id", "data"]);
+        assert!(rows[1][1].starts_with("<blob 10 byte 89504e470d0a1a0a… #"));
+        assert_ne!(rows[1][1], rows[2][1]);
+        assert!(rows[3][1].starts_with("<blob 2 byte cafe #"));
+        assert_eq!(rows[3][1], blob_text(&[0xca, 0xfe]));
+        assert_eq!(rows[4][1], "");
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap},
+    fs::File,
+    time::Instant,
+};
+
+use encoding_rs_io::DecodeReaderBytes;
+
+use super::{
+    compares::{
+        diff_fingerprints, is_related, row_hash, CmpData, CmpOption, Comparison, DiffStats,
+        Progress,
+    },
+    deserializer::{check_record, read_record},
+    dialect::CsvOption,
+    encoding::open_decoded,
+    errors::{DpdResult, LoadWarning},
+    schema::SAMPLE_ROWS,
+    CmpRslt,
+};
+
+type CsvReader = csv::Reader<DecodeReaderBytes<File, Vec<u8>>>;
+
+/// satu file csv yang dibandingkan tanpa dimuat seluruhnya ke memory
+pub struct StreamInput<'a> {
+    pub file: &'a str,
+    pub option: &'a CsvOption,
+}
+
+pub struct Streamed {
+    pub comparison: Comparison,
+    pub warnings: Vec<(String, LoadWarning)>,
+}
+
+impl StreamInput<'_> {
+    fn reader(&self) -> DpdResult<CsvReader> {
+        let encoding = self.option.resolve_encoding(self.file)?;
+        let dialect = self.option.resolve_dialect(self.file, encoding)?;
+        Ok(dialect
+            .reader_builder()
+            .from_reader(open_decoded(self.file, encoding)?))
+    }
+
+    /// panggil `f` untuk setiap record, aturan warning sama dengan `deserialize_data_csv`
+    fn for_each_record<F>(&self, mut f: F) -> DpdResult<Vec<LoadWarning>>
+    where
+        F: FnMut(usize, &[Cow<str>]) -> bool,
+    {
+        let mut reader = self.reader()?;
+        let mut warnings = vec![];
+        let mut record = csv::ByteRecord::new();
+        let mut index = 0;
+        while read_record(&mut reader, &mut record, &mut warnings) {
+            warnings.extend(check_record(&record));
+            let fields = record
+                .iter()
+                .map(String::from_utf8_lossy)
+                .collect::<Vec<_>>();
+            if !f(index, &fields) {
+                break;
+            }
+            index += 1;
+        }
+        Ok(warnings)
+    }
+
+    /// pass pertama: hanya fingerprint 8 byte per baris yang disimpan
+    fn fingerprints(&self, ignore_num: bool) -> DpdResult<(Vec<u64>, Vec<LoadWarning>)> {
+        let mut hashes = vec![];
+        let warnings = self.for_each_record(|_, fields| {
+            hashes.push(row_hash(fields, ignore_num));
+            true
+        })?;
+        Ok((hashes, warnings))
+    }
+
+    /// pass kedua: ambil isi baris yang berubah saja
+    fn fetch(&self, wanted: &BTreeSet<usize>) -> DpdResult<HashMap<usize, Vec<String>>> {
+        let mut out = HashMap::with_capacity(wanted.len());
+        let last = match wanted.iter().next_back() {
+            Some(&last) => last,
+            None => return Ok(out),
+        };
+        self.for_each_record(|index, fields| {
+            if wanted.contains(&index) {
+                out.insert(
+                    index,
+                    fields.iter().map(|c| c.clone().into_owned()).collect(),
+                );
+            }
+            index < last
+        })?;
+        Ok(out)
+    }
+
+    /// beberapa baris awal untuk perbandingan struktur kolom
+    pub fn preview(&self) -> DpdResult<CmpData> {
+        let encoding = self.option.resolve_encoding(self.file)?;
+        let dialect = self.option.resolve_dialect(self.file, encoding)?;
+        let mut rows = vec![];
+        self.for_each_record(|_, fields| {
+            rows.push(fields.iter().map(|c| c.clone().into_owned()).collect());
+            rows.len() <= SAMPLE_ROWS
+        })?;
+        let mut out = CmpData::from_records(self.file.to_owned(), rows, vec![]);
+        out.has_header = dialect.has_header;
+        out.dialect = Some(dialect);
+        out.encoding = Some(encoding);
+        Ok(out)
+    }
+}
+
+/// bandingkan 2 file csv besar: file dibaca 2 kali secara streaming,
+/// memory yang dipakai sebanding dengan jumlah baris (fingerprint) dan jumlah baris yang berubah
+pub fn compare_streaming(
+    option: CmpOption,
+    src: &StreamInput,
+    target: &StreamInput,
+    src_sheet: &str,
+    target_sheet: &str,
+) -> DpdResult<Streamed> {
+    let start = Instant::now();
+    let (old, src_warnings) = src.fingerprints(option.ignore_num)?;
+    let (new, target_warnings) = target.fingerprints(option.ignore_num)?;
+    // waktu hash termasuk membaca file
+    let mut stats = DiffStats {
+        hash: start.elapsed(),
+        ..Default::default()
+    };
+    let diff = diff_fingerprints(&option, &old, &new, &mut stats, &Progress::default())?;
+    drop((old, new));
+
+    let (wanted_src, wanted_target) = diff.changed_rows();
+    let src_rows = src.fetch(&wanted_src)?;
+    let target_rows = target.fetch(&wanted_target)?;
+    let changes = diff.changes(&option, |o, n| match (src_rows.get(&o), target_rows.get(&n)) {
+        (Some(o), Some(n)) => is_related(o, n, option.ignore_num),
+        _ => false,
+    });
+
+    let cells = |rows: &HashMap<usize, Vec<String>>, index: usize| {
+        let row = rows.get(&index).cloned().unwrap_or_default();
+        if option.ignore_num {
+            row.get(1..).unwrap_or_default().to_owned()
+        } else {
+            row
+        }
+    };
+    let out = changes
+        .into_iter()
+        .map(|change| {
+            let (rows, other, file, sheet) = if change.issrc {
+                (&src_rows, &target_rows, src.file, src_sheet)
+            } else {
+                (&target_rows, &src_rows, target.file, target_sheet)
+            };
+            CmpRslt {
+                issrc: change.issrc,
+                index: change.index,
+                tag: change.tag,
+                file: file.to_owned(),
+                sheet: sheet.to_owned(),
+                data: cells(rows, change.index),
+                pair: change.pair.map(|i| cells(other, i)),
+                moved: change.moved,
+            }
+        })
+        .collect();
+    stats.total = start.elapsed();
+
+    let warnings = src_warnings
+        .into_iter()
+        .map(|w| (src.file.to_owned(), w))
+        .chain(
+            target_warnings
+                .into_iter()
+                .map(|w| (target.file.to_owned(), w)),
+        )
+        .collect();
+    Ok(Streamed {
+        comparison: Comparison(out, stats),
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dpdcmpexcel::compares::Side;
+
+    type Row = (bool, String, usize, Vec<String>, Option<Vec<String>>, Option<(usize, usize)>);
+
+    fn rows(out: Comparison) -> Vec<Row> {
+        out.0
+            .into_iter()
+            .map(|r| (r.issrc, format!("{:?}", r.tag), r.index, r.data, r.pair, r.moved))
+            .collect()
+    }
+
+    #[test]
+    fn streaming_matches_in_memory_comparison() {
+        let dir = std::env::temp_dir();
+        let src = dir.join(format!("stream-src-{}.csv", std::process::id()));
+        let target = dir.join(format!("stream-tgt-{}.csv", std::process::id()));
+        std::fs::write(
+            &src,
+            "id,nama,kota\n1,Ani,Solo\n2,Budi,Jakarta\n3,Citra,Bandung\n4,Dedi,Medan\n5,Eka,Bogor\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &target,
+            "id,nama,kota\n1,Ani,Solo\n5,Eka,Bogor\n2,Budi,Jakarta Selatan\n4,Dedi,Medan\n6,Fajar,Depok\n",
+        )
+        .unwrap();
+        let (src_file, target_file) = (src.to_str().unwrap(), target.to_str().unwrap());
+        let csv = CsvOption::default();
+
+        for option in [
+            CmpOption::default(),
+            CmpOption {
+                detect_move: true,
+                ..Default::default()
+            },
+            CmpOption {
+                ignore_num: true,
+                ..Default::default()
+            },
+        ] {
+            let streamed = compare_streaming(
+                option,
+                &StreamInput { file: src_file, option: &csv },
+                &StreamInput { file: target_file, option: &csv },
+                "Sheet1",
+                "Sheet1",
+            )
+            .unwrap();
+            let (old, new) = (CmpData::new(&src).unwrap(), CmpData::new(&target).unwrap());
+            let full = Comparison::run(
+                option,
+                Side {
+                    rows: &old.selected_data,
+                    file: src_file,
+                    sheet: "Sheet1",
+                },
+                Side {
+                    rows: &new.selected_data,
+                    file: target_file,
+                    sheet: "Sheet1",
+                },
+                &Progress::default(),
+            )
+            .unwrap();
+            assert!(streamed.warnings.is_empty());
+            assert!(!full.0.is_empty());
+            assert_eq!(rows(streamed.comparison), rows(full), "{:?}", option);
+        }
+        std::fs::remove_file(&src).ok();
+        std::fs::remove_file(&target).ok();
+    }
+}
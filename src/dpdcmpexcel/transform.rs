@@ -0,0 +1,79 @@
+use std::fmt;
+
+use super::{
+    errors::{DpdError, DpdResult},
+    filter::FilterExpr,
+    SortKey, SortVec,
+};
+
+/// perubahan tampilan tabel yang dicatat di history `CmpData`, data asli tidak pernah diubah
+/// sehingga setiap langkah bisa di undo / redo dengan mengulang transform dari data asli
+#[derive(Debug, Clone)]
+pub enum Transform {
+    Sort(Vec<SortKey>),
+    Filter(FilterExpr),
+    HideColumn(usize),
+    Edit {
+        row: usize,
+        col: usize,
+        value: String,
+    },
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transform::Sort(keys) => {
+                write!(f, "sort")?;
+                for k in keys {
+                    write!(f, " {}{}", k.col + 1, if k.descending { "▼" } else { "▲" })?;
+                }
+                Ok(())
+            }
+            Transform::Filter(_) => write!(f, "filter"),
+            Transform::HideColumn(col) => write!(f, "sembunyikan kolom {}", col + 1),
+            Transform::Edit { row, col, value } => {
+                write!(f, "edit baris {} kolom {} = `{}`", row + 1, col + 1, value)
+            }
+        }
+    }
+}
+
+impl Transform {
+    /// `skip` jumlah baris header yang tidak ikut di sort / filter
+    pub fn apply(&self, data: &mut Vec<Vec<String>>, skip: usize) -> DpdResult<()> {
+        let skip = skip.min(data.len());
+        match self {
+            Transform::Sort(keys) => data[skip..].sort_by_keys(keys),
+            Transform::Filter(expr) => {
+                let mut idx = 0;
+                data.retain(|row| {
+                    idx += 1;
+                    idx <= skip || expr.matches(row)
+                });
+                Ok(())
+            }
+            Transform::HideColumn(col) => {
+                for row in data.iter_mut().filter(|r| *col < r.len()) {
+                    row.remove(*col);
+                }
+                Ok(())
+            }
+            Transform::Edit { row, col, value } => {
+                let cell = data
+                    .get_mut(*row)
+                    .and_then(|r| {
+                        if *col >= r.len() {
+                            r.resize(*col + 1, String::new());
+                        }
+                        r.get_mut(*col)
+                    })
+                    .ok_or_else(|| {
+                        DpdError::Processing(format!("Baris {} tidak ditemukan untuk diedit", row + 1))
+                    })?;
+                *cell = value.to_owned();
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use eframe::egui::*;
+
+use encoding_rs::Encoding;
+
+use crate::dpdcmpexcel::{
+    dialect::{display_dialect_char, parse_dialect_char, CsvDialect, DialectOverride},
+    encoding::encodings,
+};
+
+#[derive(Debug, Default)]
+pub(super) struct DialectEditor {
+    delimiter: String,
+    quote: String,
+    escape: String,
+    comment: String,
+    has_header: bool,
+    flexible: bool,
+    /// `None` berarti dideteksi otomatis saat dimuat ulang
+    encoding: Option<&'static Encoding>,
+}
+
+pub(super) enum DialectAction {
+    /// dialect dideteksi ulang dari file lalu ditimpa isian editor
+    Reload(DialectOverride),
+    Detect,
+}
+
+impl DialectEditor {
+    pub fn set(&mut self, d: &CsvDialect, encoding: Option<&'static Encoding>) {
+        self.encoding = encoding;
+        self.delimiter = display_dialect_char(Some(d.delimiter));
+        self.quote = display_dialect_char(Some(d.quote));
+        self.escape = display_dialect_char(d.escape);
+        self.comment = display_dialect_char(d.comment);
+        self.has_header = d.has_header;
+        self.flexible = d.flexible;
+    }
+
+    #[inline]
+    pub fn encoding(&self) -> Option<&'static Encoding> {
+        self.encoding
+    }
+
+    /// isian yang kosong mengikuti hasil deteksi
+    fn build(&self) -> DialectOverride {
+        DialectOverride {
+            delimiter: parse_dialect_char(&self.delimiter),
+            quote: parse_dialect_char(&self.quote),
+            escape: parse_dialect_char(&self.escape),
+            has_header: Some(self.has_header),
+            comment: parse_dialect_char(&self.comment),
+            flexible: Some(self.flexible),
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<DialectAction> {
+        let mut out = None;
+        ui.horizontal_wrapped(|ui| {
+            let char_edit = |ui: &mut Ui, label: &str, value: &mut String, hover: &str| {
+                ui.label(label);
+                ui.add(TextEdit::singleline(value).desired_width(24.0))
+                    .on_hover_text(hover);
+            };
+            char_edit(ui, "Delimiter", &mut self.delimiter, "satu karakter, `\\t` untuk tab, kosong berarti dideteksi otomatis");
+            char_edit(ui, "Quote", &mut self.quote, "karakter quote, kosong berarti dideteksi otomatis");
+            char_edit(ui, "Escape", &mut self.escape, "kosong berarti quote ganda (`\"\"`)");
+            char_edit(ui, "Comment", &mut self.comment, "baris yang diawali karakter ini diabaikan");
+            ui.checkbox(&mut self.has_header, "Header");
+            ui.checkbox(&mut self.flexible, "Flexible")
+                .on_hover_text("izinkan jumlah kolom yang berbeda di setiap baris");
+            ui.label("Encoding");
+            ComboBox::from_id_source("csv_encoding")
+                .selected_text(self.encoding.map(Encoding::name).unwrap_or("Otomatis"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.encoding, None, "Otomatis");
+                    for encoding in encodings() {
+                        ui.selectable_value(&mut self.encoding, Some(encoding), encoding.name());
+                    }
+                })
+                .response
+                .on_hover_text("Otomatis: dideteksi dari BOM / isi file");
+            ui.separator();
+            if ui
+                .button("Muat Ulang")
+                .on_hover_text("baca ulang file csv dengan dialect dan encoding diatas")
+                .clicked()
+            {
+                out = Some(DialectAction::Reload(self.build()));
+            }
+            if ui
+                .button("Deteksi Otomatis")
+                .on_hover_text("deteksi delimiter, quote dan header dari isi file")
+                .clicked()
+            {
+                out = Some(DialectAction::Detect);
+            }
+        });
+        out
+    }
+}
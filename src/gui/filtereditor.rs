@@ -0,0 +1,111 @@
+use eframe::egui::*;
+
+use crate::dpdcmpexcel::{
+    errors::DpdResult,
+    filter::{build_op, Condition, FilterExpr, Join},
+};
+
+const OPERATORS: [(&str, &str); 6] = [
+    ("=", "sama dengan"),
+    ("~", "mengandung"),
+    ("=~", "regex"),
+    ("in", "range angka min..max"),
+    ("is", "kosong"),
+    ("not", "tidak kosong"),
+];
+
+#[derive(Debug, Default, Clone)]
+struct FilterRow {
+    join: Join,
+    col: usize,
+    op: usize,
+    value: String,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct FilterEditor {
+    rows: Vec<FilterRow>,
+}
+
+impl FilterEditor {
+    #[inline]
+    pub fn clear(&mut self) {
+        self.rows.clear();
+    }
+
+    pub fn build(&self) -> DpdResult<Option<FilterExpr>> {
+        let mut chain = vec![];
+        for row in self.rows.iter() {
+            let (op, _) = OPERATORS[row.op];
+            let value = match op {
+                "is" | "not" => "empty",
+                _ => row.value.as_str(),
+            };
+            chain.push((
+                row.join,
+                Condition {
+                    col: row.col,
+                    op: build_op(op, value)?,
+                },
+            ));
+        }
+        Ok(FilterExpr::from_chain(chain))
+    }
+
+    /// `Some(expr)` jika tombol terapkan ditekan
+    pub fn ui(&mut self, ui: &mut Ui, columns: &[String]) -> Option<DpdResult<Option<FilterExpr>>> {
+        let mut remove = None;
+        for (idx, row) in self.rows.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.push_id(idx, |ui| {
+                    if idx == 0 {
+                        ui.label("WHERE");
+                    } else {
+                        ComboBox::from_id_source("join")
+                            .width(50.0)
+                            .selected_text(match row.join {
+                                Join::And => "AND",
+                                Join::Or => "OR",
+                            })
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut row.join, Join::And, "AND");
+                                ui.selectable_value(&mut row.join, Join::Or, "OR");
+                            });
+                    }
+                    ComboBox::from_id_source("col").show_index(ui, &mut row.col, columns.len(), |i| {
+                        columns[i].to_owned()
+                    });
+                    ComboBox::from_id_source("op").width(140.0).show_index(
+                        ui,
+                        &mut row.op,
+                        OPERATORS.len(),
+                        |i| format!("{} ({})", OPERATORS[i].0, OPERATORS[i].1),
+                    );
+                    let needs_value = !matches!(OPERATORS[row.op].0, "is" | "not");
+                    ui.add_enabled(needs_value, TextEdit::singleline(&mut row.value).desired_width(120.0));
+                    if ui.small_button("🗑").on_hover_text("hapus kondisi").clicked() {
+                        remove = Some(idx);
+                    }
+                });
+            });
+        }
+        if let Some(idx) = remove {
+            self.rows.remove(idx);
+        }
+
+        let mut out = None;
+        ui.horizontal(|ui| {
+            if ui.button("+ Kondisi").clicked() {
+                self.rows.push(FilterRow::default());
+            }
+            if ui
+                .add_enabled(!self.rows.is_empty(), Button::new("Terapkan Filter"))
+                .on_hover_text("filter baris tabel dengan kondisi diatas, AND lebih diutamakan dari OR")
+                .clicked()
+            {
+                out = Some(self.build());
+            }
+        });
+        out
+    }
+}
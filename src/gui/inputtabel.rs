@@ -1,17 +1,50 @@
-use std::{ops::Div, path::PathBuf};
+use std::{ops::Div, path::PathBuf, sync::Arc};
 
 use eframe::egui::*;
 
-use crate::{dpdcmpexcel::compares::*, exec_async, gui::mainwindow::thick_row};
+use crate::{
+    dpdcmpexcel::{
+        compares::*, dialect::CsvOption, errors::{DpdError, DpdResult}, fixedwidth::FixedLayout, transform::Transform, SortKey,
+    },
+    exec_async,
+    gui::mainwindow::ROW_HEIGHT,
+};
 
-use super::{DisplayGui, Message, UnWrapGui, View};
+use super::{
+    dialecteditor::{DialectAction, DialectEditor},
+    filtereditor::FilterEditor,
+    layouteditor::{LayoutAction, LayoutEditor},
+    searchbar::{highlight, mark, scroll_to_row, SearchBar},
+    show_error, DisplayGui, Message, UnWrapGui, View,
+};
 
 #[derive(Debug)]
 pub(super) struct InputTabel {
     pub(super) data: CmpData,
     pub(super) idx_sheet: usize,
     pub(super) idx_sort: usize,
-    pub(super) idx_filter: usize,
+    editing: Option<(usize, usize, String)>,
+    query: String,
+    pub(super) filter: FilterEditor,
+    pub(super) dialect: DialectEditor,
+    layout: LayoutEditor,
+    search: SearchBar,
+    /// posisi baris (tanpa header) yang perlu di-scroll pada frame berikutnya
+    scroll_to: Option<usize>,
+    /// baris (index data terpilih) yang dituju dari tabel output, ditandai sampai data berganti
+    marked: Option<usize>,
+    /// `marked` belum di-scroll karena sheet masih dimuat
+    goto_pending: bool,
+    /// keterangan data yang sedang dimuat di thread lain
+    loading: Option<String>,
+    /// naik setiap memuat / menutup, hasil thread dengan nomor lama dibuang
+    load_generation: u64,
+    /// progres dan pembatalan pemuatan yang sedang berjalan
+    load_progress: Arc<Progress>,
+    /// yang dimuat adalah sheet, data workbook sedang dipegang thread pemuat
+    loading_sheet: bool,
+    /// sheet yang datanya sedang tampil, dipilih kembali jika pemuatan sheet dibatalkan
+    shown_sheet: usize,
     message_channel: (
         std::sync::mpsc::Sender<Message>,
         std::sync::mpsc::Receiver<Message>,
@@ -23,7 +56,20 @@ impl Default for InputTabel {
             data: Default::default(),
             idx_sheet: Default::default(),
             idx_sort: Default::default(),
-            idx_filter: Default::default(),
+            editing: Default::default(),
+            query: Default::default(),
+            filter: Default::default(),
+            dialect: Default::default(),
+            layout: Default::default(),
+            search: Default::default(),
+            scroll_to: Default::default(),
+            marked: Default::default(),
+            goto_pending: Default::default(),
+            loading: Default::default(),
+            load_generation: Default::default(),
+            load_progress: Default::default(),
+            loading_sheet: Default::default(),
+            shown_sheet: Default::default(),
             message_channel: std::sync::mpsc::channel(),
         }
     }
@@ -32,8 +78,20 @@ impl Default for InputTabel {
 impl InputTabel {
     pub fn open_path(&mut self) {
         let future = rfd::AsyncFileDialog::new()
+            .add_filter(
+                "Semua Tabel",
+                &[
+                    "xlsx", "xlsb", "xlsm", "xls", "ods", "csv", "tsv", "sqlite", "sqlite3", "db",
+                    "json", "jsonl", "ndjson", "txt", "dat", "prn",
+                ],
+            )
             .add_filter("ExcelFile", &["xlsx", "xlsb", "xlsm", "xls"])
-            .set_title("Pilih Excel File Yang Akan Dibuka")
+            .add_filter("OpenDocument Spreadsheet", &["ods"])
+            .add_filter("CSV / TSV", &["csv", "tsv"])
+            .add_filter("SQLite Database", &["sqlite", "sqlite3", "db"])
+            .add_filter("JSON / JSON Lines", &["json", "jsonl", "ndjson"])
+            .add_filter("Fixed Width Text", &["txt", "dat", "prn"])
+            .set_title("Pilih File Yang Akan Dibuka")
             .set_directory(super::HOME.unwrap_or_default())
             .pick_file();
 
@@ -41,39 +99,237 @@ impl InputTabel {
         exec_async!({
             if let Some(file) = future.await {
                 message_sender
-                    .send(Message::FileOpen(CmpData::new(file.path())))
+                    .send(Message::FileChosen(file.path().to_owned()))
                     .ok();
             }
         });
     }
+
+    /// semua jalur membuka file (dialog, drag-drop, baca ulang csv) dimuat di thread lain,
+    /// hasil diterima sebagai `Message::FileOpen`
+    fn load(&mut self, path: PathBuf, option: CsvOption) {
+        self.loading = Some(format!("Membuka `{}`", path.display()));
+        self.load_progress = Default::default();
+        self.loading_sheet = false;
+        self.load_generation += 1;
+        let generation = self.load_generation;
+        let message_sender = self.message_channel.0.clone();
+        std::thread::spawn(move || {
+            message_sender
+                .send(Message::FileOpen(generation, CmpData::new_with(&path, &option)))
+                .ok();
+        });
+    }
+    fn open_layout(&mut self) {
+        let future = rfd::AsyncFileDialog::new()
+            .add_filter("Layout CSV", &["csv"])
+            .set_title("Pilih File Layout Fixed Width")
+            .set_directory(super::HOME.unwrap_or_default())
+            .pick_file();
+        let message_sender = self.message_channel.0.clone();
+        exec_async!({
+            if let Some(file) = future.await {
+                message_sender
+                    .send(Message::LayoutOpen(FixedLayout::load(file.path())))
+                    .ok();
+            }
+        });
+    }
+
+    fn save_layout(&mut self, layout: FixedLayout) {
+        let future = rfd::AsyncFileDialog::new()
+            .add_filter("Layout CSV", &["csv"])
+            .set_file_name("layout.csv")
+            .set_title("Simpan Layout Fixed Width")
+            .set_directory(super::HOME.unwrap_or_default())
+            .save_file();
+        let message_sender = self.message_channel.0.clone();
+        exec_async!({
+            if let Some(file) = future.await {
+                message_sender
+                    .send(Message::IgnoredResult(layout.save(file.path()).ok()))
+                    .ok();
+            }
+        });
+    }
+
+    /// baca ulang file text yang sedang dibuka dengan opsi baru
+    fn reload_with(&mut self, option: CsvOption) {
+        self.load(PathBuf::from(&self.data.file), option);
+    }
+
     #[inline]
     pub fn set_data(&mut self, d: CmpData) {
+        if let Some(dialect) = &d.dialect {
+            self.dialect.set(dialect, d.encoding);
+        }
+        if let Some(layout) = &d.layout {
+            self.layout.set(layout);
+        }
         self.data = d;
+        self.search.invalidate();
+        self.marked = None;
         self.refresh();
     }
 
-    #[inline]
+    /// sheet dibaca di thread lain, data dikembalikan lewat `Message::SheetLoaded`
     pub fn refresh(&mut self) {
-        if self.data.exl.is_none() {
-            ()
-        } else {
-            let sheet_selected = match self.data.sheets.get(self.idx_sheet) {
-                Some(data) => data.to_owned(),
-                None => String::new(),
-            };
-            self.data.set_selected_data(&sheet_selected).unwrap_gui();
+        self.editing = None;
+        if !self.data.is_workbook() {
+            return;
+        }
+        let sheet_selected = match self.data.sheets.get(self.idx_sheet) {
+            Some(data) => data.to_owned(),
+            None => String::new(),
+        };
+        self.load_data(format!("Memuat sheet `{}`", sheet_selected), move |data, progress| {
+            data.set_selected_data(&sheet_selected, progress)
+        });
+    }
+
+    /// query sqlite dijalankan di thread lain seperti memuat sheet
+    fn run_query(&mut self) {
+        self.editing = None;
+        let sql = self.query.to_owned();
+        self.load_data("Menjalankan query".to_owned(), move |data, progress| {
+            data.load_query(&sql, progress)
+        });
+    }
+
+    /// `job` dijalankan pada data yang dipindah ke thread lain, lalu dikembalikan
+    /// bersama hasilnya lewat `Message::SheetLoaded`
+    fn load_data<F>(&mut self, label: String, job: F)
+    where
+        F: FnOnce(&mut CmpData, &Progress) -> DpdResult<()> + Send + 'static,
+    {
+        self.loading = Some(label);
+        self.load_progress = Default::default();
+        self.loading_sheet = true;
+        self.load_generation += 1;
+        let generation = self.load_generation;
+        let progress = self.load_progress.clone();
+        let mut data = std::mem::take(&mut self.data);
+        let message_sender = self.message_channel.0.clone();
+        std::thread::spawn(move || {
+            let result = job(&mut data, &progress);
+            message_sender
+                .send(Message::SheetLoaded(generation, Box::new(data), result))
+                .ok();
+        });
+    }
+
+    /// terima hasil dari thread dialog / pemuat, dipanggil setiap frame walaupun tab tidak tampil
+    pub fn poll(&mut self, ctx: &Context) {
+        if self.loading.is_some() {
+            ctx.request_repaint();
+        }
+        if let Ok(message) = self.message_channel.1.try_recv() {
+            match message {
+                Message::FileChosen(path) => self.load(path, CsvOption::default()),
+                // hasil pemuatan lama (input ditutup / file lain dibuka) dibuang
+                Message::FileOpen(generation, _) | Message::SheetLoaded(generation, ..)
+                    if generation != self.load_generation => {}
+                Message::FileOpen(_, file) => {
+                    self.loading = None;
+                    match file {
+                        Ok(data) => self.set_data(data),
+                        Err(e) => show_error(e),
+                    }
+                }
+                Message::LayoutOpen(layout) => {
+                    let layout = layout.unwrap_gui();
+                    if !layout.columns.is_empty() {
+                        self.layout.set(&layout);
+                    }
+                }
+                Message::SheetLoaded(_, data, result) => {
+                    self.loading = None;
+                    self.data = *data;
+                    self.search.invalidate();
+                    match result {
+                        Ok(()) => self.shown_sheet = self.idx_sheet,
+                        // data sheet sebelumnya tidak diganti
+                        Err(DpdError::Cancelled) => self.idx_sheet = self.shown_sheet,
+                        Err(e) => show_error(e),
+                    }
+                }
+                _ => (),
+            }
         }
     }
 
+    #[inline]
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         self.data.close();
+        self.filter.clear();
         self.idx_sheet = 0;
+        self.shown_sheet = 0;
+        self.loading = None;
+        self.load_generation += 1;
+        self.load_progress.cancel();
+        self.marked = None;
+    }
+
+    /// dipanggil dari tabel output: pilih `sheet` lalu scroll ke baris `index`,
+    /// index adalah posisi pada data terpilih saat perbandingan (termasuk header).
+    /// `generation` versi data yang dibandingkan, `None` jika sheet dibaca tanpa transform.
+    /// hasil `false` jika data sudah berubah sehingga index tidak lagi menunjuk baris yang sama
+    pub fn goto_row(&mut self, sheet: &str, index: usize, generation: Option<u64>) -> bool {
+        let shown = !self.data.is_workbook()
+            || self.data.sheets.get(self.shown_sheet).is_some_and(|s| s == sheet);
+        let valid = match generation {
+            Some(generation) => shown && self.data.generation() == generation,
+            None => !shown || !self.data.can_undo(),
+        };
+        if !valid {
+            show_error(DpdError::Validation(format!(
+                "Tabel `{}` sudah berubah sejak dibandingkan (sheet, sort, filter, header atau edit), \
+                 bandingkan ulang untuk membuka baris {}",
+                sheet,
+                index + 1
+            )));
+            return false;
+        }
+        if !shown {
+            if let Some(idx) = self.data.sheets.iter().position(|s| s == sheet) {
+                self.idx_sheet = idx;
+                self.refresh();
+            }
+        }
+        self.marked = Some(index);
+        self.goto_pending = true;
+        true
     }
 
     #[inline]
     pub fn is_opened(&self) -> bool {
-        !self.data.exl.is_none() && !self.data.sheets.is_empty()
+        !self.data.sheets.is_empty()
+    }
+
+    /// click biasa: sort hanya berdasarkan kolom `col`, shift: tambahkan `col` sebagai kunci berikutnya,
+    /// kolom yang sudah menjadi kunci dibalik urutannya
+    pub fn toggle_sort_key(&mut self, col: usize, append: bool) {
+        let mut keys = self.data.sort_keys().to_owned();
+        let single = keys.len() == 1;
+        match keys.iter_mut().find(|k| k.col == col) {
+            Some(key) if append || single => key.descending = !key.descending,
+            _ if append => keys.push(SortKey {
+                col,
+                descending: false,
+            }),
+            _ => {
+                keys = vec![SortKey {
+                    col,
+                    descending: false,
+                }]
+            }
+        }
+        self.data.sort(&keys).unwrap_gui();
     }
 
     pub fn draw_table(&mut self, ui: &mut Ui) {
@@ -86,46 +342,97 @@ impl InputTabel {
                 .columns(Size::remainder().at_least(10.0), self.data.size.w)
                 .resizable(true)
                 .header(20.0, |mut header| {
-                    if !self.data.selected_data.is_empty() && self.data.has_header {
-                        let iter = self.data.selected_data[0].clone().into_iter();
-                        for (idx, item) in iter.enumerate() {
-                            if header
-                                .col(|ui| {
-                                    ui.heading(item);
-                                })
-                                .interact(Sense::click().union(Sense::hover()))
-                                .on_hover_text(format!(
-                                    "Click to sort the table, current sort row: {}",
-                                    idx
-                                ))
-                                .clicked()
-                            {
-                                self.data.sort(Some(idx)).unwrap_gui()
-                            }
+                    let columns = self.data.column_names();
+                    let sort_keys = self.data.sort_keys().to_owned();
+                    for (idx, item) in columns.into_iter().enumerate() {
+                        let indicator = sort_keys
+                            .iter()
+                            .position(|k| k.col == idx)
+                            .map(|pos| {
+                                let arrow = if sort_keys[pos].descending { "▼" } else { "▲" };
+                                if sort_keys.len() > 1 {
+                                    format!(" {}{}", arrow, pos + 1)
+                                } else {
+                                    format!(" {}", arrow)
+                                }
+                            })
+                            .unwrap_or_default();
+                        let response = header
+                            .col(|ui| {
+                                ui.heading(format!("{}{}", item, indicator));
+                            })
+                            .interact(Sense::click().union(Sense::hover()))
+                            .on_hover_text(
+                                "Click untuk sort berdasarkan kolom ini (click lagi untuk membalik urutan), \
+                                 Shift+Click untuk menambah kolom sort berikutnya",
+                            );
+                        if response.clicked() {
+                            let shift = response.ctx.input().modifiers.shift;
+                            self.toggle_sort_key(idx, shift);
                         }
-                    } else {
-                        header.col(|ui| {
-                            ui.heading("No Header");
+                        response.context_menu(|ui| {
+                            if ui.button("Sembunyikan kolom").clicked() {
+                                self.data.apply(Transform::HideColumn(idx)).unwrap_gui();
+                                ui.close_menu();
+                            }
                         });
                     }
                 })
-                .body(|mut body| {
+                .body(|body| {
                     if !self.data.selected_data.is_empty() {
-                        let iters = if self.data.has_header {
-                            self.data.selected_data[1..].iter().enumerate()
-                        } else {
-                            self.data.selected_data.iter().enumerate()
-                        };
-                        for (idx, item) in iters {
-                            let row_height = if thick_row(idx) { 30.0 } else { 18.0 };
-                            body.row(row_height, |mut row| {
-                                for it in item {
-                                    row.col(|ui| {
-                                        ui.wrap_text();
-                                        ui.small(it);
-                                    });
-                                }
-                            });
+                        let skip = usize::from(self.data.has_header).min(self.data.selected_data.len());
+                        let total = self.data.selected_data.len() - skip;
+                        let mut commit = None;
+                        let mut scroll_to = self.scroll_to.take();
+                        let search = self.search.search();
+                        let marked = self.marked;
+                        body.rows(ROW_HEIGHT, total, |shown, mut row| {
+                            let idx = shown + skip;
+                            let item = &self.data.selected_data[idx];
+                            for (col, it) in item.iter().enumerate() {
+                                row.col(|ui| {
+                                    if let Some(target) = scroll_to.take() {
+                                        scroll_to_row(ui, shown, target);
+                                    }
+                                    match &mut self.editing {
+                                        Some((r, c, value)) if *r == idx && *c == col => {
+                                            let edit = ui.text_edit_singleline(value);
+                                            if edit.lost_focus() {
+                                                if !ui.input().key_pressed(Key::Escape) {
+                                                    commit = Some((idx, col, value.to_owned()));
+                                                }
+                                                self.editing = None;
+                                            } else {
+                                                edit.request_focus();
+                                            }
+                                        }
+                                        _ => {
+                                            // tinggi baris tetap, isi cell tidak di-wrap
+                                            if ui
+                                                .add(
+                                                    Label::new(highlight(
+                                                        mark(RichText::new(it).small(), marked == Some(idx)),
+                                                        search.is_some_and(|s| s.cell_matches(col, it)),
+                                                    ))
+                                                    .wrap(false)
+                                                    .sense(Sense::click()),
+                                                )
+                                                .on_hover_text("Double click untuk edit cell")
+                                                .double_clicked()
+                                            {
+                                                self.editing = Some((idx, col, it.to_owned()));
+                                            }
+                                        }
+                                    }
+                                });
+                            }
+                        });
+                        if let Some((row, col, value)) = commit {
+                            if self.data.selected_data[row].get(col) != Some(&value) {
+                                self.data
+                                    .apply(Transform::Edit { row, col, value })
+                                    .unwrap_gui();
+                            }
                         }
                     }
                 })
@@ -135,25 +442,74 @@ impl InputTabel {
 
 impl View for InputTabel {
     fn ui(&mut self, ui: &mut eframe::egui::Ui) {
-        {
-            match self.message_channel.1.try_recv() {
-                Ok(message) => match message {
-                    Message::FileOpen(file) => self.set_data(file.unwrap_gui()),
-                    _ => (),
-                },
-                Err(_) => {
-                    ();
+        if let Some(loading) = &self.loading {
+            let mut cancel = false;
+            ui.centered_and_justified(|ui| {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    if self.load_progress.is_cancelled() {
+                        ui.label("Membatalkan...");
+                        return;
+                    }
+                    ui.label(loading);
+                    if let Some(fraction) = self.load_progress.fraction() {
+                        ui.add(
+                            ProgressBar::new(fraction)
+                                .show_percentage()
+                                .desired_width(200.0),
+                        );
+                    }
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+            if cancel {
+                self.load_progress.cancel();
+                // file yang sedang dibuka langsung dilepas, data sheet ditunggu kembali dari thread
+                if !self.loading_sheet {
+                    self.loading = None;
+                    self.load_generation += 1;
                 }
             }
+            return;
+        }
+        if self.is_opened() && !ui.ctx().wants_keyboard_input() {
+            let (undo, redo) = {
+                let input = ui.input();
+                let cmd = input.modifiers.command;
+                (
+                    cmd && !input.modifiers.shift && input.key_pressed(Key::Z),
+                    cmd && (input.key_pressed(Key::Y)
+                        || (input.modifiers.shift && input.key_pressed(Key::Z))),
+                )
+            };
+            if undo {
+                self.data.undo().unwrap_gui();
+            } else if redo {
+                self.data.redo().unwrap_gui();
+            }
         }
         if !self.is_opened() {
-            if !ui.ctx().input().raw.dropped_files.is_empty() {
-                if let Some(path) = ui.ctx().input().raw.dropped_files.clone().first() {
-                    self.set_data(
-                        CmpData::new(&path.path.as_ref().unwrap_or(&PathBuf::default()))
-                            .unwrap_gui(),
-                    );
+            let dropped = ui
+                .ctx()
+                .input()
+                .raw
+                .dropped_files
+                .first()
+                .and_then(|f| f.path.clone());
+            if let Some(path) = dropped {
+                self.load(path, CsvOption::default());
+                return;
+            }
+            let pasted = ui.input().events.iter().find_map(|e| match e {
+                Event::Paste(text) => Some(text.to_owned()),
+                _ => None,
+            });
+            if let Some(text) = pasted {
+                match CmpData::from_clipboard(&text) {
+                    Ok(data) => self.set_data(data),
+                    Err(e) => show_error(e),
                 }
+                return;
             }
             if !ui.ui_contains_pointer() {
                 return;
@@ -168,7 +524,7 @@ impl View for InputTabel {
                         );
                         ui.label(
                             eframe::egui::RichText::new(
-                                "📂\nDROP OR CLICK\nTO OPEN FILE\nOR PASTE FROM WEB",
+                                "📂\nDROP OR CLICK\nTO OPEN FILE\nOR PASTE FROM WEB (CTRL+V)",
                             )
                             .size(rect.height().div(10f32)),
                         )
@@ -189,58 +545,183 @@ impl View for InputTabel {
                     ui.colored_label(Color32::BLUE, self.data.file.display_gui_text())
                         .on_hover_text(&self.data.file);
                     ui.separator();
-                    let sheets: &Vec<String> = self.data.sheets.as_ref();
-                    let cmb_changed = ComboBox::from_label("sheetexcel")
-                        .show_index(ui, &mut self.idx_sheet, sheets.len(), |i| {
-                            sheets[i].to_owned()
-                        })
-                        .changed();
-                    if cmb_changed {
-                        self.refresh();
+                    if self.data.is_workbook() {
+                        let sheets: &Vec<String> = self.data.sheets.as_ref();
+                        let cmb_changed = ComboBox::from_label("sheetexcel")
+                            .show_index(ui, &mut self.idx_sheet, sheets.len(), |i| {
+                                sheets[i].to_owned()
+                            })
+                            .changed();
+                        if cmb_changed {
+                            self.marked = None;
+                            self.refresh();
+                        }
+                    } else if let Some(encoding) = self.data.encoding {
+                        ui.label(format!("CSV | {}", encoding.name()));
                     }
                 });
                 ui.separator();
                 ui.horizontal(|ui| {
-                    ui.checkbox(&mut self.data.has_header, "Has Header")
-                        .on_hover_text("Check if excel has Header");
+                    let mut has_header = self.data.has_header;
+                    if ui
+                        .checkbox(&mut has_header, "Has Header")
+                        .on_hover_text("Check if tabel has Header")
+                        .changed()
+                    {
+                        self.data.set_has_header(has_header).unwrap_gui();
+                    }
                     ui.separator();
+                    let columns = self.data.column_names();
+                    ComboBox::from_label("SortBy Kolom")
+                        .show_index(ui, &mut self.idx_sort, columns.len(), |i| {
+                            columns[i].to_owned()
+                        })
+                        .on_hover_text("pilih kolom untuk sort, atau click header tabel");
                     if ui
                         .button("Sort")
-                        .on_hover_text("Sort by max row available")
+                        .on_hover_text("sort hanya berdasarkan kolom terpilih, click lagi untuk membalik urutan")
                         .clicked()
                     {
-                        self.data.sort(None).unwrap_gui();
+                        self.toggle_sort_key(self.idx_sort, false);
                     }
-                    if ComboBox::from_label("SortBy Row")
-                        .show_index(ui, &mut self.idx_sort, self.data.size.w, |i| i.to_string())
-                        .on_hover_text("sort table by row, or you can click the header table")
-                        .changed()
+                    if ui
+                        .button("+ Then By")
+                        .on_hover_text("tambahkan kolom terpilih sebagai kunci sort berikutnya (sama dengan Shift+Click header)")
+                        .clicked()
                     {
-                        self.data.sort(Some(self.idx_sort)).unwrap_gui();
+                        self.toggle_sort_key(self.idx_sort, true);
                     }
                     ui.separator();
                     if ui
-                        .button("Filter")
-                        .on_hover_text("filter by max row available")
+                        .add_enabled(self.data.can_undo(), Button::new("↶ Undo"))
+                        .on_hover_text(match self.data.history().last() {
+                            Some(t) => format!("Undo {} (Ctrl+Z)", t),
+                            None => "Undo (Ctrl+Z)".to_owned(),
+                        })
+                        .clicked()
+                    {
+                        self.data.undo().unwrap_gui();
+                    }
+                    if ui
+                        .add_enabled(self.data.can_redo(), Button::new("↷ Redo"))
+                        .on_hover_text("Redo (Ctrl+Y / Ctrl+Shift+Z)")
                         .clicked()
                     {
-                        self.data.filter(None).unwrap_gui();
+                        self.data.redo().unwrap_gui();
                     }
-                    if ComboBox::from_label("FilterBy Row")
-                        .show_index(ui, &mut self.idx_filter, self.data.size.w, |i| {
-                            i.to_string()
-                        })
-                        .on_hover_text("filter table by max row selected")
-                        .changed()
+                    if ui
+                        .add_enabled(self.data.can_undo(), Button::new("Reset"))
+                        .on_hover_text("Kembalikan tabel ke data asli, semua sort, filter, kolom tersembunyi dan edit dibuang")
+                        .clicked()
                     {
-                        self.data.filter(Some(self.idx_sort)).unwrap_gui();
+                        self.editing = None;
+                        self.data.reset().unwrap_gui();
                     }
-                    ui.separator();
-                    if ui.button("Undo Change").clicked() {
-                        self.refresh();
+                });
+                if self.data.dialect.is_some() {
+                    let action = CollapsingHeader::new("CSV Dialect")
+                        .show(ui, |ui| self.dialect.ui(ui))
+                        .body_returned
+                        .flatten();
+                    if let Some(action) = action {
+                        self.reload_with(CsvOption {
+                            overrides: match action {
+                                DialectAction::Reload(overrides) => overrides,
+                                DialectAction::Detect => Default::default(),
+                            },
+                            encoding: self.dialect.encoding(),
+                            ..Default::default()
+                        });
+                    }
+                }
+                if self.data.dialect.is_some() || self.data.layout.is_some() {
+                    let applied = self.data.layout.is_some();
+                    let action = CollapsingHeader::new("Fixed Width Layout")
+                        .default_open(applied)
+                        .show(ui, |ui| self.layout.ui(ui, applied))
+                        .body_returned
+                        .flatten();
+                    match action {
+                        Some(LayoutAction::Apply(layout)) => self.reload_with(CsvOption {
+                            encoding: self.data.encoding,
+                            layout: Some(layout),
+                            ..Default::default()
+                        }),
+                        Some(LayoutAction::Clear) => self.reload_with(CsvOption {
+                            encoding: self.data.encoding,
+                            ..Default::default()
+                        }),
+                        Some(LayoutAction::Open) => self.open_layout(),
+                        Some(LayoutAction::Save(layout)) => self.save_layout(layout),
+                        None => (),
+                    }
+                }
+                if self.data.db.is_some() {
+                    CollapsingHeader::new("SQL Query").show(ui, |ui| {
+                        ui.add(
+                            TextEdit::multiline(&mut self.query)
+                                .code_editor()
+                                .desired_rows(3)
+                                .desired_width(f32::INFINITY)
+                                .hint_text("SELECT * FROM siswa WHERE kelas = '7A'"),
+                        );
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(!self.query.trim().is_empty(), Button::new("Jalankan"))
+                                .on_hover_text("ganti tabel dengan hasil query (database dibuka read-only)")
+                                .clicked()
+                            {
+                                self.run_query();
+                            }
+                            if ui
+                                .button("Muat Tabel")
+                                .on_hover_text("kembali ke isi tabel yang dipilih")
+                                .clicked()
+                            {
+                                self.refresh();
+                            }
+                        });
+                    });
+                }
+                if !self.data.warnings.is_empty() {
+                    CollapsingHeader::new(
+                        RichText::new(format!("Peringatan ({})", self.data.warnings.len()))
+                            .color(Color32::YELLOW),
+                    )
+                    .id_source("load_warnings")
+                    .show(ui, |ui| {
+                        ScrollArea::vertical()
+                            .id_source("load_warnings_scroll")
+                            .max_height(120.0)
+                            .show(ui, |ui| {
+                                for warning in &self.data.warnings {
+                                    ui.label(warning.to_string());
+                                }
+                            });
+                    });
+                }
+                CollapsingHeader::new("Filter").show(ui, |ui| {
+                    let columns = self.data.column_names();
+                    if let Some(expr) = self.filter.ui(ui, &columns) {
+                        if let Some(expr) = expr.unwrap_gui() {
+                            self.data.filter(&expr).unwrap_gui();
+                        }
                     }
                 });
                 ui.separator();
+                let skip = usize::from(self.data.has_header).min(self.data.selected_data.len());
+                let body = &self.data.selected_data[skip..];
+                let columns = self.data.column_names();
+                let jump = self.search.ui(ui, &columns, body.len(), self.data.generation(), |search| {
+                    search.find_rows(body.iter().map(Vec::as_slice))
+                });
+                if jump.is_some() {
+                    self.scroll_to = jump;
+                }
+                if std::mem::take(&mut self.goto_pending) {
+                    self.scroll_to = self.marked.map(|row| row.saturating_sub(skip));
+                }
+                ui.separator();
                 self.draw_table(ui);
             }
         }
@@ -0,0 +1,92 @@
+use eframe::egui::*;
+
+use crate::dpdcmpexcel::fixedwidth::{FixedColumn, FixedLayout};
+
+#[derive(Debug, Default)]
+pub(super) struct LayoutEditor {
+    columns: Vec<FixedColumn>,
+}
+
+pub(super) enum LayoutAction {
+    Apply(FixedLayout),
+    Open,
+    Save(FixedLayout),
+    /// baca ulang file sebagai csv biasa
+    Clear,
+}
+
+impl LayoutEditor {
+    pub fn set(&mut self, layout: &FixedLayout) {
+        self.columns = layout.columns.to_owned();
+    }
+
+    fn build(&self) -> FixedLayout {
+        FixedLayout {
+            columns: self.columns.to_owned(),
+        }
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui, applied: bool) -> Option<LayoutAction> {
+        let mut out = None;
+        let mut removed = None;
+        Grid::new("fixed_width_layout")
+            .striped(true)
+            .num_columns(5)
+            .show(ui, |ui| {
+                ui.strong("Nama");
+                ui.strong("Mulai");
+                ui.strong("Lebar");
+                ui.strong("Trim");
+                ui.end_row();
+                for (idx, col) in self.columns.iter_mut().enumerate() {
+                    ui.add(TextEdit::singleline(&mut col.name).desired_width(120.0));
+                    ui.add(DragValue::new(&mut col.start).clamp_range(1..=usize::MAX))
+                        .on_hover_text("posisi karakter pertama, mulai dari 1");
+                    ui.add(DragValue::new(&mut col.width).clamp_range(1..=usize::MAX))
+                        .on_hover_text("jumlah karakter");
+                    ui.checkbox(&mut col.trim, "")
+                        .on_hover_text("buang spasi di awal dan akhir nilai");
+                    if ui.small_button("🗑").on_hover_text("hapus kolom").clicked() {
+                        removed = Some(idx);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(idx) = removed {
+            self.columns.remove(idx);
+        }
+        ui.horizontal_wrapped(|ui| {
+            if ui.button("+ Kolom").clicked() {
+                let start = self.build().line_width() + 1;
+                self.columns.push(FixedColumn {
+                    name: format!("Kolom {}", self.columns.len() + 1),
+                    start,
+                    width: 10,
+                    trim: true,
+                });
+            }
+            ui.separator();
+            if ui
+                .add_enabled(!self.columns.is_empty(), Button::new("Terapkan"))
+                .on_hover_text("baca ulang file sebagai fixed width dengan layout diatas")
+                .clicked()
+            {
+                out = Some(LayoutAction::Apply(self.build()));
+            }
+            if ui.button("Buka Layout").clicked() {
+                out = Some(LayoutAction::Open);
+            }
+            if ui
+                .add_enabled(!self.columns.is_empty(), Button::new("Simpan Layout"))
+                .on_hover_text("simpan layout ke file csv untuk dipakai ulang")
+                .clicked()
+            {
+                out = Some(LayoutAction::Save(self.build()));
+            }
+            if applied && ui.button("Baca Sebagai CSV").clicked() {
+                out = Some(LayoutAction::Clear);
+            }
+        });
+        out
+    }
+}
@@ -1,18 +1,26 @@
-use std::cell::RefCell;
+use std::{
+    cell::RefCell,
+    sync::{
+        mpsc::{Receiver, TryRecvError},
+        Arc,
+    },
+};
 
 use eframe::egui::style::Margin;
 use eframe::egui::*;
 
-use crate::dpdcmpexcel::compares::Comparison;
-use crate::dpdcmpexcel::DpdError;
+use crate::dpdcmpexcel::batch::{compare_pairs, merge, workbook_pairs};
+use crate::dpdcmpexcel::compares::{CmpOption, Comparison, Progress, Side, Table};
+use crate::dpdcmpexcel::errors::DpdResult;
+use crate::dpdcmpexcel::schema::SchemaDiff;
+use crate::dpdcmpexcel::{CmpData, DpdError};
 
 use super::inputtabel::InputTabel;
 use super::outputtabel::OutputTable;
-use super::{UnWrapGui, View};
+use super::{show_error, Message, UnWrapGui, View};
 
-pub fn thick_row(row_index: usize) -> bool {
-    row_index % 6 == 0
-}
+/// tinggi tetap setiap baris tabel, hanya baris yang terlihat yang di-layout
+pub const ROW_HEIGHT: f32 = 22.0;
 
 #[derive(Debug, PartialEq, Eq, Default)]
 pub(super) enum ShowTable {
@@ -22,7 +30,93 @@ pub(super) enum ShowTable {
     Output,
 }
 
-#[derive(Debug, Default)]
+/// perbandingan yang sedang berjalan di thread lain
+#[derive(Debug)]
+pub(super) struct CompareJob {
+    progress: Arc<Progress>,
+    receiver: Receiver<Message>,
+    inputs: [ComparedInput; 2],
+}
+
+/// sheet dan versi data (`CmpData::generation`) input saat dibandingkan,
+/// index baris hasil hanya berlaku selama data input belum berubah
+#[derive(Debug, Clone)]
+pub(super) struct ComparedInput {
+    sheet: String,
+    generation: u64,
+}
+
+/// salinan input yang dibawa ke thread perbandingan
+struct InputSnapshot {
+    file: String,
+    sheet: String,
+    has_header: bool,
+    generation: u64,
+    /// dibagi dengan tabel input, tidak di-copy
+    rows: Table,
+}
+
+impl InputSnapshot {
+    fn new(input: &InputTabel) -> Self {
+        Self {
+            file: input.data.file.to_owned(),
+            sheet: input.data.sheets[input.idx_sheet].to_owned(),
+            has_header: input.data.has_header,
+            generation: input.data.generation(),
+            rows: input.data.selected_data.clone(),
+        }
+    }
+
+    /// tabel terpilih tanpa membuka file, untuk perbandingan struktur kolom
+    fn compared(&self) -> ComparedInput {
+        ComparedInput {
+            sheet: self.sheet.to_owned(),
+            generation: self.generation,
+        }
+    }
+
+    fn table(&self) -> CmpData {
+        let mut data = CmpData::default().with_rows(&self.sheet, self.rows.clone());
+        data.has_header = self.has_header;
+        data
+    }
+
+    /// workbook dibuka ulang di thread perbandingan untuk membaca sheet lainnya
+    fn reopen(self) -> DpdResult<CmpData> {
+        let mut book = CmpData::new(&self.file)?;
+        book.selected_data = self.rows;
+        book.has_header = self.has_header;
+        Ok(book)
+    }
+}
+
+/// semua sheet bernama sama dibaca lalu dibandingkan paralel, struktur kolom
+/// yang ditampilkan adalah milik sheet yang sedang dipilih
+fn compare_workbooks(
+    option: CmpOption,
+    src: InputSnapshot,
+    target: InputSnapshot,
+    progress: &Progress,
+) -> DpdResult<(Comparison, SchemaDiff)> {
+    let (src_sheet, target_sheet) = (src.sheet.to_owned(), target.sheet.to_owned());
+    let mut src = src.reopen()?;
+    progress.check()?;
+    let mut target = target.reopen()?;
+    progress.check()?;
+    let (pairs, schemas): (Vec<_>, Vec<_>) =
+        workbook_pairs(&mut src, &src_sheet, &mut target, &target_sheet, progress)?
+            .into_iter()
+            .unzip();
+    progress.check()?;
+    let schema = pairs
+        .iter()
+        .position(|p| p.src_sheet == src_sheet)
+        .and_then(|i| schemas.get(i).cloned())
+        .unwrap_or_default();
+    Ok((merge(compare_pairs(option, &pairs, progress)?), schema))
+}
+
+#[derive(Debug)]
 pub(crate) struct CenterWindow {
     pub(super) output: RefCell<OutputTable>,
     pub(super) input_source: RefCell<InputTabel>,
@@ -30,10 +124,43 @@ pub(crate) struct CenterWindow {
     pub(super) algoritma: usize,
     pub(super) show_table: ShowTable,
     pub(super) ignore_num: bool,
+    pub(super) detect_move: bool,
+    pub(super) hide_move: bool,
+    pub(super) all_sheets: bool,
+    pub(super) job: Option<CompareJob>,
+    /// input dari hasil perbandingan yang sedang tampil, `[sumber, target]`
+    pub(super) compared: Option<[ComparedInput; 2]>,
+}
+
+impl Default for CenterWindow {
+    /// pilihan awal mengikuti `CmpOption::default()`
+    fn default() -> Self {
+        let option = CmpOption::default();
+        Self {
+            output: Default::default(),
+            input_source: Default::default(),
+            input_target: Default::default(),
+            algoritma: match option.algoritm {
+                similar::Algorithm::Myers => 0,
+                similar::Algorithm::Patience => 1,
+                similar::Algorithm::Lcs => 2,
+            },
+            show_table: Default::default(),
+            ignore_num: option.ignore_num,
+            detect_move: option.detect_move,
+            hide_move: option.hide_move,
+            all_sheets: false,
+            job: None,
+            compared: None,
+        }
+    }
 }
 
 impl CenterWindow {
     pub fn ui(&mut self, ctx: &eframe::egui::Context) {
+        self.poll_job(ctx);
+        self.input_source.get_mut().poll(ctx);
+        self.input_target.get_mut().poll(ctx);
         eframe::egui::SidePanel::left("panel_config_left")
             .resizable(true)
             .frame(Frame::none().inner_margin(Margin::symmetric(10f32, 10f32)))
@@ -45,36 +172,167 @@ impl CenterWindow {
                 ShowTable::Target => self.input_target.get_mut().ui(ui),
                 ShowTable::Output => self.output.get_mut().ui(ui),
             });
+        self.open_goto();
+    }
+
+    /// baris output yang di-click dibuka pada tabel input asalnya
+    fn open_goto(&mut self) {
+        use super::outputtabel::Targets;
+        if let Some((target, sheet, index)) = self.output.get_mut().take_goto() {
+            let (input, show, side) = match target {
+                Targets::Source => (self.input_source.get_mut(), ShowTable::Source, 0),
+                Targets::Target => (self.input_target.get_mut(), ShowTable::Target, 1),
+            };
+            // sheet lain pada perbandingan semua sheet dibaca tanpa transform
+            let generation = self
+                .compared
+                .as_ref()
+                .map(|c| &c[side])
+                .filter(|c| c.sheet == sheet)
+                .map(|c| c.generation);
+            if input.goto_row(&sheet, index, generation) {
+                self.show_table = show;
+            }
+        }
     }
+    fn option(&self) -> CmpOption {
+        CmpOption {
+            algoritm: match self.algoritma {
+                0 => similar::Algorithm::Myers,
+                1 => similar::Algorithm::Patience,
+                2 => similar::Algorithm::Lcs,
+                _ => Err(DpdError::Processing(
+                    "Indexing on Algoritm Chosen".to_owned(),
+                ))
+                .unwrap_gui(),
+            },
+            ignore_num: self.ignore_num,
+            detect_move: self.detect_move,
+            hide_move: self.hide_move,
+            ..Default::default()
+        }
+    }
+
+    fn can_compare_all(&self) -> bool {
+        self.input_source.borrow().data.is_workbook() && self.input_target.borrow().data.is_workbook()
+    }
+
+    /// data input di-copy lalu dibandingkan di thread lain, hasil diterima lewat `poll_job`
     pub fn compare(&mut self) {
-        let src = self.input_source.borrow();
-        let target = self.input_target.borrow();
-        if src.is_opened() && target.is_opened() {
-            let (_src, _tgt) = Comparison::run(
-                match self.algoritma {
-                    0 => similar::Algorithm::Myers,
-                    1 => similar::Algorithm::Patience,
-                    2 => similar::Algorithm::Lcs,
-                    _ => Err(DpdError::Processing(
-                        "Indexing on Algoritm Chosen".to_owned(),
-                    ))
-                    .unwrap_gui(),
-                },
-                src.data.selected_data.as_ref(),
-                target.data.selected_data.as_ref(),
-                &src.data.sheets[src.idx_sheet].clone(),
-                &src.data.file,
-                &target.data.file,
-                self.ignore_num
-            )
-            .unwrap_gui()
-            .get_data();
-
-            let output = self.output.get_mut();
-            output.set_src(_src);
-            output.set_tgt(_tgt);
-            self.show_table = ShowTable::Output;
-        } else {
+        if self.job.is_some() || !self.is_ready_compare() {
+            return;
+        }
+        let option = self.option();
+        let all_sheets = self.all_sheets && self.can_compare_all();
+        let src = InputSnapshot::new(&self.input_source.borrow());
+        let target = InputSnapshot::new(&self.input_target.borrow());
+
+        let progress = Arc::new(Progress::default());
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let worker = progress.clone();
+        let inputs = [src.compared(), target.compared()];
+        std::thread::spawn(move || {
+            let result = if all_sheets {
+                compare_workbooks(option, src, target, &worker)
+            } else {
+                let schema = SchemaDiff::compare(&src.table(), &target.table());
+                worker.add_total(Comparison::work(src.rows.len(), target.rows.len()));
+                Comparison::run(
+                    option,
+                    Side {
+                        rows: &src.rows,
+                        file: &src.file,
+                        sheet: &src.sheet,
+                    },
+                    Side {
+                        rows: &target.rows,
+                        file: &target.file,
+                        sheet: &target.sheet,
+                    },
+                    &worker,
+                )
+                .map(|comparison| (comparison, schema))
+            };
+            sender.send(Message::Compared(result)).ok();
+        });
+        self.job = Some(CompareJob {
+            progress,
+            receiver,
+            inputs,
+        });
+    }
+
+    fn poll_job(&mut self, ctx: &Context) {
+        let result = match &self.job {
+            Some(job) => match job.receiver.try_recv() {
+                Ok(Message::Compared(result)) => result,
+                Ok(_) => return,
+                Err(TryRecvError::Empty) => {
+                    ctx.request_repaint();
+                    return;
+                }
+                Err(TryRecvError::Disconnected) => Err(DpdError::Processing(
+                    "Thread perbandingan berhenti tanpa hasil".to_owned(),
+                )),
+            },
+            None => return,
+        };
+        let inputs = self.job.take().map(|job| job.inputs);
+        match result {
+            Ok((comparison, schema)) => {
+                self.compared = inputs;
+                let stats = comparison.stats().to_owned();
+                let (_src, _tgt) = comparison.get_data();
+
+                let output = self.output.get_mut();
+                output.set_schema(schema);
+                output.set_stats(stats);
+                output.set_src(_src);
+                output.set_tgt(_tgt);
+                self.show_table = ShowTable::Output;
+            }
+            Err(DpdError::Cancelled) => (),
+            Err(e) => show_error(e),
+        }
+    }
+
+    /// job yang dibatalkan langsung dilepas, thread berhenti sendiri pada pengecekan berikutnya
+    fn job_progress(&mut self, ui: &mut Ui) {
+        let mut cancelled = false;
+        if let Some(job) = &self.job {
+            ui.horizontal(|ui| {
+                match (job.progress.sheets(), job.progress.fraction()) {
+                    (Some((done, total)), _) => {
+                        ui.add(
+                            ProgressBar::new(done as f32 / total as f32)
+                                .text(format!("memuat sheet {} / {}", done + 1, total))
+                                .desired_width(ui.available_width() - 80.0),
+                        );
+                    }
+                    (None, Some(fraction)) => {
+                        ui.add(
+                            ProgressBar::new(fraction)
+                                .show_percentage()
+                                .desired_width(ui.available_width() - 80.0),
+                        );
+                    }
+                    (None, None) => {
+                        ui.spinner();
+                        ui.label("membuka workbook...");
+                    }
+                }
+                if ui
+                    .button("Cancel")
+                    .on_hover_text("Batalkan perbandingan")
+                    .clicked()
+                {
+                    job.progress.cancel();
+                    cancelled = true;
+                }
+            });
+        }
+        if cancelled {
+            self.job = None;
         }
     }
     fn side_bar(&mut self, uiwin: &mut eframe::egui::Ui) {
@@ -112,9 +370,22 @@ impl CenterWindow {
             ui.separator();
             ui.add_enabled_ui(self.is_ready_compare(), |ui| {
                 ui.checkbox(&mut self.ignore_num, "Ignore Numbering");
+                ui.checkbox(&mut self.detect_move, "Detect Moved Rows")
+                    .on_hover_text("Baris identik yang hanya berpindah posisi ditandai sebagai MOVED");
+                ui.add_enabled_ui(self.detect_move, |ui| {
+                    ui.checkbox(&mut self.hide_move, "Hide Moved Rows")
+                        .on_hover_text("Jangan tampilkan baris MOVED di tabel output");
+                });
+                let can_compare_all = self.can_compare_all();
+                ui.add_enabled_ui(can_compare_all, |ui| {
+                    ui.checkbox(&mut self.all_sheets, "Compare All Sheets")
+                        .on_hover_text("Bandingkan semua sheet dengan nama yang sama secara paralel")
+                        .on_disabled_hover_text("Hanya untuk input workbook excel / ods / sqlite");
+                });
                 ui.separator();
                 if ui
-                    .add(
+                    .add_enabled(
+                        self.job.is_none(),
                         eframe::egui::Button::new(
                             RichText::new("COMPARE INPUT")
                                 .strong()
@@ -128,6 +399,7 @@ impl CenterWindow {
                 {
                     self.compare()
                 }
+                self.job_progress(ui);
             });
         });
         uiwin.separator();
@@ -167,18 +439,21 @@ impl CenterWindow {
                     .on_hover_text("Swap Source Table with Target Table")
                     .clicked()
                 {
+                    // data beserta history transform ikut berpindah, tidak dimuat ulang
                     std::mem::swap(&mut self.input_target, &mut self.input_source);
-                    self.input_source.get_mut().refresh();
-                    self.input_target.get_mut().refresh();
                 }
             },
         );
     }
 
     pub fn is_ready_compare(&self) -> bool {
-        self.input_source.borrow().is_opened() && self.input_target.borrow().is_opened()
+        let (src, target) = (self.input_source.borrow(), self.input_target.borrow());
+        src.is_opened() && target.is_opened() && !src.is_loading() && !target.is_loading()
     }
     pub fn close_current(&mut self) {
+        if let Some(job) = self.job.take() {
+            job.progress.cancel();
+        }
         self.output.get_mut().clear();
         self.input_source.get_mut().clear();
         self.input_target.get_mut().clear();
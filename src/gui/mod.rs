@@ -1,6 +1,11 @@
+pub mod dialecteditor;
+pub mod filtereditor;
 pub mod inputtabel;
+pub mod layouteditor;
 pub mod mainwindow;
+pub mod outputfilter;
 pub mod outputtabel;
+pub mod searchbar;
 
 use crate::dpdcmpexcel::errors::DpdResult;
 
@@ -131,7 +136,19 @@ impl eframe::App for Application {
 }
 
 pub enum Message {
-    FileOpen(DpdResult<crate::dpdcmpexcel::CmpData>),
+    /// file dipilih dari dialog, dimuat lewat jalur yang sama dengan drag-drop
+    FileChosen(std::path::PathBuf),
+    /// hasil membuka file, diawali nomor pemuatan (`load_generation`) saat thread dimulai
+    FileOpen(u64, DpdResult<crate::dpdcmpexcel::CmpData>),
+    LayoutOpen(DpdResult<crate::dpdcmpexcel::fixedwidth::FixedLayout>),
+    Compared(
+        DpdResult<(
+            crate::dpdcmpexcel::Comparison,
+            crate::dpdcmpexcel::schema::SchemaDiff,
+        )>,
+    ),
+    /// data dikembalikan dari thread pemuat sheet / query, beserta nomor pemuatan dan hasilnya
+    SheetLoaded(u64, Box<crate::dpdcmpexcel::CmpData>, DpdResult<()>),
     #[allow(unused)]
     ReturnDialog(bool),
     #[allow(unused)]
@@ -147,21 +164,24 @@ impl<T: Default + ?Sized> UnWrapGui<T> for DpdResult<T> {
         match self {
             Ok(k) => k,
             Err(e) => {
-                exec_async!({
-                    rfd::MessageDialog::new()
-                        .set_level(rfd::MessageLevel::Error)
-                        .set_title("Got Error!")
-                        .set_buttons(rfd::MessageButtons::Ok)
-                        .set_description(&e.to_string())
-                        .show()
-                });
-
+                show_error(e);
                 Default::default()
             }
         }
     }
 }
 
+fn show_error(e: crate::dpdcmpexcel::DpdError) {
+    exec_async!({
+        rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Error)
+            .set_title("Got Error!")
+            .set_buttons(rfd::MessageButtons::Ok)
+            .set_description(&e.to_string())
+            .show()
+    });
+}
+
 trait DisplayGui {
     fn display_gui_text(&self) -> eframe::egui::RichText;
 }
@@ -202,3 +222,16 @@ impl DisplayGui for similar::ChangeTag {
         }
     }
 }
+
+impl DisplayGui for crate::dpdcmpexcel::CmpRslt {
+    #[inline]
+    fn display_gui_text(&self) -> eframe::egui::RichText {
+        use eframe::egui::RichText;
+        use eframe::egui::epaint::Color32;
+        match self.moved {
+            Some(_) => RichText::from(self.tag_text()).color(Color32::LIGHT_BLUE).raised(),
+            None if self.is_modified() => RichText::from(self.tag_text()).color(Color32::YELLOW).raised(),
+            None => self.tag.display_gui_text(),
+        }
+    }
+}
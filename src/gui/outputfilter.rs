@@ -0,0 +1,85 @@
+use eframe::egui::*;
+
+use crate::dpdcmpexcel::{
+    resultfilter::{ChangeKind, PerKind, ResultFilter},
+    search::Search,
+};
+
+/// saringan tabel output: jenis perubahan, kolom yang berubah, teks, dan pengelompokan per sheet
+#[derive(Debug, Default)]
+pub(super) struct OutputFilter {
+    filter: ResultFilter,
+    /// 0 berarti semua kolom
+    column: usize,
+    text: String,
+    regex: bool,
+    pub group_sheet: bool,
+    error: Option<String>,
+}
+
+impl OutputFilter {
+    #[inline]
+    pub fn filter(&self) -> &ResultFilter {
+        &self.filter
+    }
+
+    /// `counts` jumlah baris setiap `ChangeKind`, hasil `true` jika tampilan perlu disusun ulang
+    pub fn ui(&mut self, ui: &mut Ui, columns: &[String], counts: &PerKind<usize>) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            for kind in ChangeKind::ALL {
+                let label = format!("{} ({})", kind.label(), counts.get(kind));
+                changed |= ui
+                    .checkbox(self.filter.kinds.get_mut(kind), label)
+                    .changed();
+            }
+            ui.separator();
+            changed |= ui
+                .checkbox(&mut self.group_sheet, "Kelompokkan per Sheet")
+                .changed();
+        });
+        ui.horizontal(|ui| {
+            changed |= ComboBox::from_label("Kolom Berubah")
+                .width(120.0)
+                .show_index(ui, &mut self.column, columns.len() + 1, |i| match i {
+                    0 => "Semua Kolom".to_owned(),
+                    i => columns[i - 1].to_owned(),
+                })
+                .on_hover_text("hanya baris MODIFIED yang isi kolom ini berubah")
+                .changed();
+            ui.separator();
+            let text_changed = ui
+                .add(
+                    TextEdit::singleline(&mut self.text)
+                        .hint_text("Saring teks...")
+                        .desired_width(160.0),
+                )
+                .changed();
+            let regex_changed = ui.checkbox(&mut self.regex, "Regex").changed();
+            if text_changed || regex_changed {
+                match Search::new(&self.text, self.regex, None) {
+                    Ok(search) => {
+                        self.error = None;
+                        self.filter.text = search;
+                    }
+                    Err(e) => {
+                        self.error = Some(e.to_string());
+                        self.filter.text = None;
+                    }
+                }
+                changed = true;
+            }
+            if let Some(e) = &self.error {
+                ui.colored_label(Color32::RED, "regex tidak valid")
+                    .on_hover_text(e);
+            }
+            ui.separator();
+            if ui.button("Reset").clicked() {
+                *self = Default::default();
+                changed = true;
+            }
+        });
+        self.filter.column = self.column.checked_sub(1);
+        changed
+    }
+}
@@ -1,24 +1,67 @@
 #![allow(unused)]
 
 use crate::{
-    dpdcmpexcel::{deserializer::convert_csv_to_excel, CmpRslt, Comparison, LimitedVec},
-    gui::mainwindow::thick_row,
+    dpdcmpexcel::{
+        compares::DiffStats,
+        search::Search,
+        deserializer::{convert_csv_to_excel, convert_to_html},
+        inline::Fragment,
+        resultfilter::{group_by_sheet, ChangeKind, PerKind},
+        schema::SchemaDiff,
+        CmpRslt, Comparison, LimitedVec,
+    },
+    gui::mainwindow::ROW_HEIGHT,
 };
 use eframe::egui::{collapsing_header::HeaderResponse, *};
 
-use super::{DisplayGui, UnWrapGui, View};
+use super::{
+    outputfilter::OutputFilter,
+    searchbar::{highlight, scroll_to_row, SearchBar},
+    DisplayGui, UnWrapGui, View,
+};
 
-#[derive(Debug, Default, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub enum Targets {
     #[default]
     Source,
     Target,
 }
+
+/// baris tabel output setelah disaring, `Item` adalah posisi pada list yang tampil
+#[derive(Debug)]
+enum ViewRow {
+    Sheet {
+        name: String,
+        rows: usize,
+        collapsed: bool,
+    },
+    Item(usize),
+}
+
 #[derive(Debug)]
 pub(super) struct OutputTable {
     src: Vec<CmpRslt>,
     tgt: Vec<CmpRslt>,
+    schema: SchemaDiff,
+    stats: Option<DiffStats>,
     show_table: Targets,
+    filter: OutputFilter,
+    /// baris yang tampil, disusun ulang hanya jika data / saringan berubah
+    view: Vec<ViewRow>,
+    view_dirty: bool,
+    view_version: u64,
+    /// sheet yang disembunyikan saat dikelompokkan
+    collapsed: Vec<String>,
+    /// jumlah baris setiap `ChangeKind` dan jumlah kolom terbanyak pada list yang tampil
+    counts: PerKind<usize>,
+    width: usize,
+    search: SearchBar,
+    /// posisi pada `view` yang perlu di-scroll
+    scroll_to: Option<usize>,
+    /// posisi baris output yang sedang dipilih pada list yang tampil
+    selected: Option<usize>,
+    /// baris yang perlu dibuka di tabel input, diambil lewat `take_goto`
+    goto: Option<(Targets, String, usize)>,
     message_channel: (
         std::sync::mpsc::Sender<super::Message>,
         std::sync::mpsc::Receiver<super::Message>,
@@ -30,7 +73,20 @@ impl Default for OutputTable {
         Self {
             src: Default::default(),
             tgt: Default::default(),
+            schema: Default::default(),
+            stats: Default::default(),
             show_table: Default::default(),
+            filter: Default::default(),
+            view: Default::default(),
+            view_dirty: Default::default(),
+            view_version: Default::default(),
+            collapsed: Default::default(),
+            counts: Default::default(),
+            width: Default::default(),
+            search: Default::default(),
+            scroll_to: Default::default(),
+            selected: Default::default(),
+            goto: Default::default(),
             message_channel: std::sync::mpsc::channel(),
         }
     }
@@ -39,20 +95,136 @@ impl Default for OutputTable {
 impl OutputTable {
     #[inline]
     pub fn set_src(&mut self, src: Vec<CmpRslt>) {
-        self.src = src.to_owned()
+        self.src = src.to_owned();
+        self.view_dirty = true;
+        self.selected = None;
     }
     #[inline]
     pub fn set_tgt(&mut self, tgt: Vec<CmpRslt>) {
-        self.tgt = tgt.to_owned()
+        self.tgt = tgt.to_owned();
+        self.view_dirty = true;
+        self.selected = None;
+    }
+    #[inline]
+    pub fn set_schema(&mut self, schema: SchemaDiff) {
+        self.schema = schema
+    }
+    #[inline]
+    pub fn set_stats(&mut self, stats: DiffStats) {
+        self.stats = Some(stats)
     }
     #[inline]
     pub fn clear(&mut self) {
         self.tgt.clear();
         self.src.clear();
+        self.schema.0.clear();
+        self.stats = None;
+        self.collapsed.clear();
+        self.view_dirty = true;
+        self.scroll_to = None;
+        self.selected = None;
+        self.goto = None;
+    }
+
+    /// `(tabel, sheet, index baris)` yang dipilih user untuk dibuka di tabel input
+    #[inline]
+    pub fn take_goto(&mut self) -> Option<(Targets, String, usize)> {
+        self.goto.take()
+    }
+
+    #[inline]
+    fn shown(&self) -> &Vec<CmpRslt> {
+        match self.show_table {
+            Targets::Source => &self.src,
+            Targets::Target => &self.tgt,
+        }
+    }
+
+    /// pilih baris output `pos` lalu buka baris asalnya di tabel input
+    fn open_change(&mut self, pos: usize) {
+        if let Some(item) = self.shown().get(pos) {
+            self.goto = Some((self.show_table, item.sheet.to_owned(), item.index));
+            self.selected = Some(pos);
+        }
+    }
+
+    /// pindah ke perubahan sebelum / sesudah baris yang dipilih tanpa meninggalkan tabel output,
+    /// hanya baris yang lolos saringan
+    fn step_change(&mut self, step: isize) {
+        let rows = self
+            .view
+            .iter()
+            .enumerate()
+            .filter_map(|(pos, row)| match row {
+                ViewRow::Item(idx) => Some((pos, *idx)),
+                ViewRow::Sheet { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        if rows.is_empty() {
+            return;
+        }
+        let len = rows.len() as isize;
+        let current = self
+            .selected
+            .and_then(|sel| rows.iter().position(|(_, idx)| *idx == sel));
+        let next = match current {
+            Some(i) => (i as isize + step).rem_euclid(len),
+            None if step > 0 => 0,
+            None => len - 1,
+        } as usize;
+        let (pos, idx) = rows[next];
+        self.selected = Some(idx);
+        self.scroll_to = Some(pos);
+    }
+
+    fn rebuild_view(&mut self) {
+        let items = self.shown();
+        let mut counts = PerKind::<usize>::default();
+        for item in items.iter() {
+            *counts.get_mut(item.kind()) += 1;
+        }
+        let width = items.iter().map(|i| i.data.len()).max().unwrap_or_default();
+        let positions = self.filter.filter().apply(items);
+        let view = if self.filter.group_sheet {
+            group_by_sheet(items, &positions)
+                .into_iter()
+                .flat_map(|(name, rows)| {
+                    let collapsed = self.collapsed.contains(&name);
+                    let head = ViewRow::Sheet {
+                        rows: rows.len(),
+                        collapsed,
+                        name,
+                    };
+                    let rows = if collapsed { vec![] } else { rows };
+                    std::iter::once(head).chain(rows.into_iter().map(ViewRow::Item))
+                })
+                .collect()
+        } else {
+            positions.into_iter().map(ViewRow::Item).collect()
+        };
+        self.view = view;
+        self.counts = counts;
+        self.width = width;
+        self.view_version += 1;
+        self.view_dirty = false;
+    }
+
+    fn toggle_sheet(&mut self, name: &str) {
+        match self.collapsed.iter().position(|s| s == name) {
+            Some(pos) => {
+                self.collapsed.remove(pos);
+            }
+            None => self.collapsed.push(name.to_owned()),
+        }
+        self.view_dirty = true;
     }
 
     pub fn on_sidebar(&mut self, ui: &mut Ui) {
         ui.small("Click `COMPARE INPUT` untuk membedakan antara 2 input excel yang sudah di dibuka, and otomatis window table akan berpaling ke tabel output. dimana output tabel hasil perbadaan didapatkan");
+        if let Some(stats) = &self.stats {
+            ui.separator();
+            ui.small(stats.to_string());
+        }
         if !self.src.is_empty() && !self.tgt.is_empty() {
             ui.wrap_text();
             ui.separator();
@@ -60,6 +232,13 @@ impl OutputTable {
                 self.save(Targets::Source);
                 self.save(Targets::Target);
             };
+            if ui
+                .button("Save HTML")
+                .on_hover_text("Simpan output ke HTML, cell yang berubah ditandai per kata/karakter")
+                .clicked()
+            {
+                self.save_html();
+            };
             ui.separator();
         }
     }
@@ -70,7 +249,7 @@ impl OutputTable {
             .iter()
             .map(|(s)| {
                 let mut data = vec![];
-                data.extend([s.index.to_string(), s.file.to_owned(), s.sheet.to_owned()]);
+                data.extend([s.number().to_string(), s.file.to_owned(), s.sheet.to_owned()]);
                 data.extend(s.data.to_owned());
                 data
             })
@@ -80,7 +259,7 @@ impl OutputTable {
             .iter()
             .map(|t| {
                 let mut data = vec![];
-                data.extend([t.index.to_string(), t.file.to_owned(), t.sheet.to_owned()]);
+                data.extend([t.number().to_string(), t.file.to_owned(), t.sheet.to_owned()]);
                 data.extend(t.data.to_owned());
                 data
             })
@@ -89,6 +268,7 @@ impl OutputTable {
         let data = [tgt.as_slice(), src.as_slice()].concat();
         let fname = "OUTPUT_DIFF.xlsx".to_owned();
         let sheet = self.src[0].sheet.to_owned();
+        let schema = self.schema.clone();
 
         let future = rfd::AsyncFileDialog::new()
             .add_filter("ExcelFile", &["xlsx", "xlsb", "xlsm", "xls"])
@@ -101,7 +281,31 @@ impl OutputTable {
             if let Some(file) = future.await {
                 message_sender
                     .send(super::Message::IgnoredResult(
-                        convert_csv_to_excel(data, file.path(), sheet).ok(),
+                        convert_csv_to_excel(data, file.path(), sheet, &schema).ok(),
+                    ))
+                    .ok();
+            }
+        });
+    }
+}
+
+impl OutputTable {
+    pub fn save_html(&mut self) {
+        let src = self.src.clone();
+        let tgt = self.tgt.clone();
+        let schema = self.schema.clone();
+        let future = rfd::AsyncFileDialog::new()
+            .add_filter("HtmlFile", &["html", "htm"])
+            .set_file_name("OUTPUT_DIFF.html")
+            .set_title("Save output to HTML File")
+            .set_directory(super::HOME.unwrap_or_default())
+            .save_file();
+        let message_sender = self.message_channel.0.clone();
+        crate::exec_async!({
+            if let Some(file) = future.await {
+                message_sender
+                    .send(super::Message::IgnoredResult(
+                        convert_to_html(&src, &tgt, &schema, file.path()).ok(),
                     ))
                     .ok();
             }
@@ -112,17 +316,111 @@ impl OutputTable {
 impl View for OutputTable {
     fn ui(&mut self, ui: &mut Ui) {
         use Targets::{Source, Target};
+        let show_table = self.show_table;
         ui.horizontal(|ui| {
             ui.radio_value(&mut self.show_table, Source, "SHOW SUMBER");
             ui.radio_value(&mut self.show_table, Target, "SHOW TARGET");
+            ui.separator();
+            let has_rows = !self.view.is_empty();
+            if ui
+                .add_enabled(has_rows, Button::new("◀ Perubahan"))
+                .on_hover_text("perubahan sebelumnya")
+                .clicked()
+            {
+                self.step_change(-1);
+            }
+            if ui
+                .add_enabled(has_rows, Button::new("Perubahan ▶"))
+                .on_hover_text("perubahan berikutnya")
+                .clicked()
+            {
+                self.step_change(1);
+            }
+            if let Some(pos) = self.selected {
+                if ui
+                    .button("Lihat di Tabel Input")
+                    .on_hover_text("buka sheet dan baris asal perubahan yang dipilih")
+                    .clicked()
+                {
+                    self.open_change(pos);
+                }
+            }
         });
+        if show_table != self.show_table {
+            self.selected = None;
+            self.view_dirty = true;
+        }
+        if self.view_dirty {
+            self.rebuild_view();
+        }
         ui.separator();
-        ui.push_id("table_output_show", |ui| {
-            ui.vertical(|ui| match self.show_table {
-                Targets::Source => table_body(ui, &self.src, Color32::GREEN),
-                Targets::Target => table_body(ui, &self.tgt, Color32::BLUE),
-            })
+        if !self.schema.is_empty() {
+            CollapsingHeader::new(
+                RichText::new(format!("PERUBAHAN STRUKTUR KOLOM ({})", self.schema.0.len()))
+                    .color(Color32::YELLOW)
+                    .strong(),
+            )
+            .default_open(true)
+            .show(ui, |ui| {
+                for change in self.schema.0.iter() {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(change.label()).monospace().strong());
+                        ui.label(change.description());
+                    });
+                }
+            });
+            ui.separator();
+        }
+        // output tidak memiliki header, kolom data diberi nama berdasarkan posisinya
+        let columns = (1..=self.width).map(|i| format!("Kolom {}", i)).collect::<Vec<_>>();
+        let total = ChangeKind::ALL.iter().map(|&k| self.counts.get(k)).sum::<usize>();
+        CollapsingHeader::new(format!("Filter Output ({} dari {} baris)", self.view.len(), total))
+            .show(ui, |ui| {
+                if self.filter.ui(ui, &columns, &self.counts) {
+                    self.view_dirty = true;
+                }
+            });
+        ui.separator();
+        let (items, color) = match self.show_table {
+            Targets::Source => (&self.src, Color32::GREEN),
+            Targets::Target => (&self.tgt, Color32::BLUE),
+        };
+        let view = &self.view;
+        let jump = self.search.ui(ui, &columns, view.len(), self.view_version, |search| {
+            search.find_rows(view.iter().map(|row| match row {
+                ViewRow::Item(idx) => items[*idx].data.as_slice(),
+                ViewRow::Sheet { .. } => &[],
+            }))
         });
+        if jump.is_some() {
+            self.scroll_to = jump;
+        }
+        ui.separator();
+        let scroll_to = self.scroll_to.take();
+        let clicked = ui
+            .push_id("table_output_show", |ui| {
+                ui.vertical(|ui| {
+                    table_body(
+                        ui,
+                        items,
+                        view,
+                        color,
+                        self.search.search(),
+                        self.selected,
+                        scroll_to,
+                    )
+                })
+                .inner
+            })
+            .inner;
+        match clicked.and_then(|pos| self.view.get(pos)) {
+            Some(ViewRow::Item(idx)) => self.open_change(*idx),
+            Some(ViewRow::Sheet { name, .. }) => {
+                let name = name.to_owned();
+                self.toggle_sheet(&name);
+            }
+            None => (),
+        }
     }
 }
 
@@ -138,33 +436,124 @@ fn table_header(mut row: egui_extras::TableRow) {
 }
 
 #[inline]
-fn table_body(ui: &mut Ui, items: &Vec<CmpRslt>, color: Color32) {
+fn table_body(
+    ui: &mut Ui,
+    items: &[CmpRslt],
+    view: &[ViewRow],
+    color: Color32,
+    search: Option<&Search>,
+    selected: Option<usize>,
+    mut scroll_to: Option<usize>,
+) -> Option<usize> {
+    let mut clicked = None;
     egui_extras::TableBuilder::new(ui)
         .striped(true)
         .cell_layout(Layout::left_to_right(Align::Center))
         .columns(egui_extras::Size::remainder().at_least(10.0), HEADING.len())
         .resizable(true)
         .header(20.0, table_header)
-        .body(|mut body| {
-            for (idx, item) in items.iter().enumerate() {
-                body.row(30.0, |mut row| {
-                    row.col(|ui| {
+        .body(|body| {
+            body.rows(ROW_HEIGHT, view.len(), |pos, mut row| {
+                let idx = match &view[pos] {
+                    ViewRow::Item(idx) => *idx,
+                    ViewRow::Sheet {
+                        name,
+                        rows,
+                        collapsed,
+                    } => {
+                        row.col(|ui| {
+                            if let Some(target) = scroll_to.take() {
+                                scroll_to_row(ui, pos, target);
+                            }
+                            let arrow = if *collapsed { "▶" } else { "▼" };
+                            if ui
+                                .selectable_label(false, RichText::new(format!("{} SHEET", arrow)).strong())
+                                .on_hover_text("Click untuk membuka / menutup kelompok sheet ini")
+                                .clicked()
+                            {
+                                clicked = Some(pos);
+                            }
+                        });
+                        row.col(|_| ());
+                        row.col(|_| ());
+                        row.col(|ui| {
+                            ui.label(RichText::new(name).strong());
+                        });
+                        row.col(|ui| {
+                            ui.label(format!("{} perubahan", rows));
+                        });
+                        return;
+                    }
+                };
+                let item = &items[idx];
+                row.col(|ui| {
+                    if let Some(target) = scroll_to.take() {
+                        scroll_to_row(ui, pos, target);
+                    }
+                    if item.is_modified() || item.is_moved() {
+                        ui.label(item.display_gui_text());
+                    } else {
                         ui.colored_label(color, item.tag.display_gui_text());
-                    });
-                    row.col(|ui| {
-                        ui.colored_label(color, item.index.to_string());
-                    });
-                    row.col(|ui| {
-                        ui.colored_label(color, item.file.display_gui_text());
-                    });
-                    row.col(|ui| {
-                        ui.colored_label(color, &item.sheet);
-                    });
-                    row.col(|ui| {
-                        ui.style_mut().wrap = Some(false);
-                        ui.label(item.data.display_gui_text());
-                    });
+                    }
                 });
-            }
+                row.col(|ui| {
+                    // baris hasil pencarian ditandai pada kolom nomor
+                    let matched = search.is_some_and(|s| s.row_matches(&item.data));
+                    let text = highlight(RichText::new(item.number().to_string()).color(color), matched);
+                    if ui
+                        .selectable_label(selected == Some(idx), text)
+                        .on_hover_text("Click untuk membuka baris ini di tabel input")
+                        .clicked()
+                    {
+                        clicked = Some(pos);
+                    }
+                });
+                row.col(|ui| {
+                    ui.colored_label(color, item.file.display_gui_text());
+                });
+                row.col(|ui| {
+                    ui.colored_label(color, &item.sheet);
+                });
+                row.col(|ui| {
+                    ui.style_mut().wrap = Some(false);
+                    let label = match item.inline_diff() {
+                        Some(cells) => Label::new(inline_diff_job(ui, &cells)),
+                        None => Label::new(item.data.display_gui_text()),
+                    };
+                    if ui.add(label.sense(Sense::click())).clicked() {
+                        clicked = Some(pos);
+                    }
+                });
+            });
         });
+    clicked
+}
+
+/// cell yang berubah: bagian yang dihapus dicoret merah, yang ditambah hijau
+fn inline_diff_job(ui: &Ui, cells: &[Vec<Fragment>]) -> text::LayoutJob {
+    use similar::ChangeTag;
+    let font_id = TextStyle::Body.resolve(ui.style());
+    let normal = ui.visuals().text_color();
+    let mut job = text::LayoutJob::default();
+    for cell in cells {
+        for frag in cell {
+            let format = match frag.tag {
+                ChangeTag::Equal => TextFormat::simple(font_id.clone(), normal),
+                ChangeTag::Delete => TextFormat {
+                    font_id: font_id.clone(),
+                    color: Color32::RED,
+                    strikethrough: Stroke::new(1.0, Color32::RED),
+                    ..Default::default()
+                },
+                ChangeTag::Insert => TextFormat {
+                    font_id: font_id.clone(),
+                    color: Color32::GREEN,
+                    ..Default::default()
+                },
+            };
+            job.append(&frag.text, 0.0, format);
+        }
+        job.append("..", 0.0, TextFormat::simple(font_id.clone(), normal));
+    }
+    job
 }
@@ -0,0 +1,183 @@
+use eframe::egui::*;
+
+use crate::dpdcmpexcel::search::Search;
+
+use super::mainwindow::ROW_HEIGHT;
+
+/// kotak pencarian dan "ke baris N" untuk tabel input dan output
+#[derive(Debug, Default)]
+pub(super) struct SearchBar {
+    text: String,
+    regex: bool,
+    /// 0 berarti semua kolom
+    column: usize,
+    goto: String,
+    search: Option<Search>,
+    error: Option<String>,
+    matches: Vec<usize>,
+    current: Option<usize>,
+    /// pencarian diulang jika query atau versi data berubah
+    key: Option<(String, bool, usize, u64)>,
+}
+
+impl SearchBar {
+    #[inline]
+    pub fn search(&self) -> Option<&Search> {
+        self.search.as_ref()
+    }
+
+    /// baris hasil pencarian yang sedang dipilih
+    #[inline]
+    pub fn current_row(&self) -> Option<usize> {
+        self.current.and_then(|i| self.matches.get(i).copied())
+    }
+
+    /// paksa pencarian diulang pada frame berikutnya, misal setelah data diganti
+    #[inline]
+    pub fn invalidate(&mut self) {
+        self.key = None;
+    }
+
+    /// `find` mengembalikan posisi baris yang cocok, `version` berubah setiap data berubah.
+    /// hasil `Some(row)` adalah posisi baris yang perlu di-scroll oleh tabel
+    pub fn ui<F>(
+        &mut self,
+        ui: &mut Ui,
+        columns: &[String],
+        total_rows: usize,
+        version: u64,
+        find: F,
+    ) -> Option<usize>
+    where
+        F: FnOnce(&Search) -> Vec<usize>,
+    {
+        let mut jump = None;
+        let mut step = 0isize;
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                TextEdit::singleline(&mut self.text)
+                    .hint_text("Cari...")
+                    .desired_width(160.0),
+            );
+            if response.lost_focus() && ui.input().key_pressed(Key::Enter) {
+                step = if ui.input().modifiers.shift { -1 } else { 1 };
+                response.request_focus();
+            }
+            ui.checkbox(&mut self.regex, "Regex");
+            ComboBox::from_id_source("search_column")
+                .width(120.0)
+                .show_index(ui, &mut self.column, columns.len() + 1, |i| match i {
+                    0 => "Semua Kolom".to_owned(),
+                    i => columns[i - 1].to_owned(),
+                });
+            if ui
+                .add_enabled(!self.matches.is_empty(), Button::new("◀"))
+                .on_hover_text("hasil sebelumnya (Shift+Enter)")
+                .clicked()
+            {
+                step = -1;
+            }
+            if ui
+                .add_enabled(!self.matches.is_empty(), Button::new("▶"))
+                .on_hover_text("hasil berikutnya (Enter)")
+                .clicked()
+            {
+                step = 1;
+            }
+            match (&self.error, &self.search) {
+                (Some(e), _) => {
+                    ui.colored_label(Color32::RED, "regex tidak valid").on_hover_text(e);
+                }
+                (None, Some(_)) => {
+                    ui.label(match self.current {
+                        Some(i) => format!("{} / {}", i + 1, self.matches.len()),
+                        None => format!("{} hasil", self.matches.len()),
+                    });
+                }
+                (None, None) => (),
+            }
+            ui.separator();
+            let response = ui.add(
+                TextEdit::singleline(&mut self.goto)
+                    .hint_text("baris")
+                    .desired_width(50.0),
+            );
+            let entered = response.lost_focus() && ui.input().key_pressed(Key::Enter);
+            if ui.button("Ke Baris").clicked() || entered {
+                match self.goto.trim().parse::<usize>() {
+                    Ok(n) if n >= 1 && n <= total_rows => jump = Some(n - 1),
+                    _ => {
+                        self.goto.clear();
+                    }
+                }
+            }
+        });
+
+        let key = (
+            self.text.to_owned(),
+            self.regex,
+            self.column,
+            version,
+        );
+        if self.key.as_ref() != Some(&key) {
+            self.key = Some(key);
+            self.current = None;
+            self.matches.clear();
+            let column = self.column.checked_sub(1);
+            match Search::new(&self.text, self.regex, column) {
+                Ok(search) => {
+                    self.error = None;
+                    if let Some(search) = &search {
+                        self.matches = find(search);
+                    }
+                    self.search = search;
+                }
+                Err(e) => {
+                    self.error = Some(e.to_string());
+                    self.search = None;
+                }
+            }
+        }
+        if step != 0 && !self.matches.is_empty() {
+            let len = self.matches.len() as isize;
+            let next = match self.current {
+                Some(i) => (i as isize + step).rem_euclid(len),
+                None if step > 0 => 0,
+                None => len - 1,
+            };
+            self.current = Some(next as usize);
+            jump = self.current_row();
+        }
+        jump
+    }
+}
+
+/// dipanggil dari dalam cell baris yang terlihat (`shown`), tabel di-scroll sehingga
+/// baris `target` berada di tengah. posisi dihitung dari tinggi baris yang tetap
+pub(super) fn scroll_to_row(ui: &mut Ui, shown: usize, target: usize) {
+    let step = ROW_HEIGHT + ui.spacing().item_spacing.y;
+    let top = ui.max_rect().top() + (target as f32 - shown as f32) * step;
+    let rect = Rect::from_min_size(pos2(ui.max_rect().left(), top), vec2(1.0, ROW_HEIGHT));
+    ui.scroll_to_rect(rect, Some(Align::Center));
+    ui.ctx().request_repaint();
+}
+
+/// baris tujuan navigasi dari tabel output diberi latar biru
+pub(super) fn mark(text: RichText, marked: bool) -> RichText {
+    if marked {
+        text.background_color(Color32::from_rgb(90, 140, 230))
+            .color(Color32::WHITE)
+    } else {
+        text
+    }
+}
+
+/// cell hasil pencarian diberi latar kuning
+pub(super) fn highlight(text: RichText, matched: bool) -> RichText {
+    if matched {
+        text.background_color(Color32::from_rgb(255, 214, 0))
+            .color(Color32::BLACK)
+    } else {
+        text
+    }
+}
@@ -1,29 +1,41 @@
 #![windows_subsystem = "windows"]
+mod cli;
 mod dpdcmpexcel;
 mod gui;
 
 fn main() -> dpdcmpexcel::errors::DpdResult<()> {
-    let (source, target, isgui) = getargs();
-    if !isgui {
-        run_without_ui(source.unwrap(), target.unwrap())?;
-    } else {
-        run_with_ui()?;
+    let parsed = cli::CliArgs::parse(std::env::args().skip(1));
+    if !matches!(parsed, Ok(None)) {
+        attach_console();
+    }
+    match parsed {
+        Ok(Some(args)) => cli::run(args)?,
+        Ok(None) => run_with_ui()?,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
     }
     Ok(())
 }
 
-fn getargs() -> (Option<String>, Option<String>, bool) {
-    match (std::env::args().nth(1), std::env::args().nth(2)) {
-        (Some(o), Some(b)) => (Some(o), Some(b), false),
-        (Some(_), None) => (None, None, true),
-        (None, Some(_)) => (None, None, true),
-        (None, None) => (None, None, true),
+/// aplikasi dibuild dengan subsystem "windows" sehingga tidak memiliki console,
+/// mode cli menumpang console yang menjalankannya atau membuat console baru
+#[cfg(windows)]
+fn attach_console() {
+    use winapi::um::{
+        consoleapi::AllocConsole,
+        wincon::{AttachConsole, ATTACH_PARENT_PROCESS},
+    };
+    unsafe {
+        if AttachConsole(ATTACH_PARENT_PROCESS) == 0 {
+            AllocConsole();
+        }
     }
 }
 
-fn run_without_ui(source: String, target: String) -> dpdcmpexcel::errors::DpdResult<()> {
-    Err(dpdcmpexcel::errors::DpdError::Processing(format!("todo! run_without_ui implementation {}{}", source, target)))
-}
+#[cfg(not(windows))]
+fn attach_console() {}
 
 fn run_with_ui() -> dpdcmpexcel::errors::DpdResult<()> {
     let icon = image::load_from_memory_with_format(